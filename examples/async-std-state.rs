@@ -0,0 +1,126 @@
+//! A version of the state.rs and shutdown.rs patterns, using the async_std runtime and async-std compatible channels.
+//!
+//! Can be run using: `cargo run --example async-std-state --no-default-features --features="dyn-bus async-std-executor async-std-channels async-std-attributes"`
+
+use bus::StateBus;
+use lifeline::prelude::*;
+use message::{CounterEvent, CounterState, Shutdown};
+use service::CounterService;
+
+#[async_std::main]
+pub async fn main() -> anyhow::Result<()> {
+    let bus = StateBus::default();
+    let _service = CounterService::spawn(&bus)?;
+
+    let tx_event = bus.tx::<CounterEvent>()?;
+    let mut rx_state = bus.rx::<CounterState>()?;
+    let rx_shutdown = bus.rx::<Shutdown>()?;
+
+    drop(bus);
+
+    tx_event.broadcast(CounterEvent::Increment).await?;
+    tx_event.broadcast(CounterEvent::Increment).await?;
+
+    // the watch receiver gets the latest state
+    while let Ok(state) = rx_state.recv().await {
+        println!("Counter state: {:?}", state);
+        if state.count == 2 {
+            break;
+        }
+    }
+
+    tx_event.broadcast(CounterEvent::Stop).await?;
+
+    // the oneshot channel delivers a single shutdown message
+    let shutdown = rx_shutdown.await?;
+    println!("Counter service stopped at count: {}", shutdown.count);
+
+    Ok(())
+}
+
+/// These are the messages which our application uses to communicate.
+mod message {
+    /// Events are carried by a broadcast channel, so many services can subscribe.
+    #[derive(Debug, Clone)]
+    pub enum CounterEvent {
+        Increment,
+        Stop,
+    }
+
+    /// The state is carried by a watch channel, which stores the latest value.
+    #[derive(Debug, Clone, Default)]
+    pub struct CounterState {
+        pub count: usize,
+    }
+
+    /// The shutdown message is carried by a oneshot channel.
+    #[derive(Debug)]
+    pub struct Shutdown {
+        pub count: usize,
+    }
+}
+
+/// This is the lifeline bus.
+/// The bus carries channels (senders/receivers).
+mod bus {
+    use crate::message::{CounterEvent, CounterState, Shutdown};
+    use futures_channel::oneshot;
+    use lifeline::prelude::*;
+
+    lifeline_bus!(pub struct StateBus);
+
+    impl Message<StateBus> for CounterEvent {
+        type Channel = async_broadcast::Sender<Self>;
+    }
+
+    impl Message<StateBus> for CounterState {
+        type Channel = async_watch::Sender<Self>;
+    }
+
+    impl Message<StateBus> for Shutdown {
+        type Channel = oneshot::Sender<Self>;
+    }
+}
+
+/// This is the service.
+/// The service is a spawnable task that launches from the bus.
+mod service {
+    use super::bus::StateBus;
+    use crate::message::{CounterEvent, CounterState, Shutdown};
+    use lifeline::prelude::*;
+
+    pub struct CounterService {
+        _greet: Lifeline,
+    }
+
+    impl Service for CounterService {
+        type Bus = StateBus;
+        type Lifeline = anyhow::Result<Self>;
+
+        fn spawn(bus: &Self::Bus) -> Self::Lifeline {
+            let mut rx = bus.rx::<CounterEvent>()?;
+            let tx_state = bus.tx::<CounterState>()?;
+            let tx_shutdown = bus.tx::<Shutdown>()?;
+
+            let _greet = Self::try_task("run", async move {
+                let mut state = CounterState::default();
+
+                while let Ok(event) = rx.recv().await {
+                    match event {
+                        CounterEvent::Increment => {
+                            state.count += 1;
+                            tx_state.send(state.clone())?;
+                        }
+                        CounterEvent::Stop => break,
+                    }
+                }
+
+                tx_shutdown
+                    .send(Shutdown { count: state.count })
+                    .map_err(|_| anyhow::anyhow!("shutdown receiver dropped"))
+            });
+
+            Ok(Self { _greet })
+        }
+    }
+}
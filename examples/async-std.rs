@@ -1,6 +1,6 @@
 //! A copy of the hello.rs example, using the async_std runtime
 //!
-//! Can be run using: `cargo run --example async-std --no-default-features --features="dyn-bus async-std-executor async-std-channels"`
+//! Can be run using: `cargo run --example async-std --no-default-features --features="dyn-bus async-std-executor async-std-channels async-std-attributes"`
 
 use bus::ExampleBus;
 use lifeline::prelude::*;
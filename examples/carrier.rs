@@ -121,9 +121,8 @@ mod bus {
 
             let lifeline = Self::try_task("from_main", async move {
                 while let Some(msg) = rx_main.recv().await {
-                    match msg {
-                        MainSend::HelloSubsurface => tx_sub.send(SubsurfaceRecv::Hello {}).await?,
-                        _ => {}
+                    if let MainSend::HelloSubsurface = msg {
+                        tx_sub.send(SubsurfaceRecv::Hello {}).await?;
                     }
                 }
 
@@ -30,12 +30,11 @@ async fn associated(mut rx: impl Receiver<ExampleMessage>) {
 mod sender {
     use crate::receiver::ExampleReceiver;
     use async_trait::async_trait;
-    use lifeline::error::SendError;
+    use lifeline::error::{SendError, TrySendError};
     use lifeline::{impl_channel_clone, Channel, Sender};
     use std::{fmt::Debug, marker::PhantomData};
 
     /// Define a dummy Sender type that implements clone
-
     pub struct ExampleSender<T> {
         _t: PhantomData<T>,
     }
@@ -70,6 +69,10 @@ mod sender {
         async fn send(&mut self, value: T) -> Result<(), SendError<T>> {
             ExampleSender::send(value).map_err(|value| SendError::Return(value))
         }
+
+        fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+            ExampleSender::send(value).map_err(TrySendError::Closed)
+        }
     }
 
     // Implement a 'clone' operation for `bus.rx::<T>()`
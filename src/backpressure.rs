@@ -0,0 +1,772 @@
+//! Backpressure policies, which decide what happens to a message when its channel is full.
+//!
+//! By default, the channel backend decides: `mpsc` senders wait for capacity, and `broadcast` senders overwrite the oldest message.
+//! Channels can opt in to lifeline-level policies by wrapping the channel type in [Backpressure](./struct.Backpressure.html).
+//! The policy is then configured per message type with [Bus::backpressure](../trait.Bus.html#method.backpressure), alongside the channel capacity.
+//!
+//! Dropped messages are logged, and counted by the bus:
+//! ```
+//! use lifeline::prelude::*;
+//! use lifeline::backpressure::{Backpressure, BackpressurePolicy};
+//! use tokio::sync::mpsc;
+//!
+//! lifeline_bus!(pub struct ExampleBus);
+//!
+//! #[derive(Debug)]
+//! struct ExampleTelemetry(usize);
+//! impl Message<ExampleBus> for ExampleTelemetry {
+//!     type Channel = Backpressure<mpsc::Sender<Self>>;
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     use lifeline::{Receiver, Sender};
+//!
+//!     let bus = ExampleBus::default();
+//!     bus.capacity::<ExampleTelemetry>(2)?;
+//!     bus.backpressure::<ExampleTelemetry>(BackpressurePolicy::DropOldest)?;
+//!
+//!     let mut tx = bus.tx::<ExampleTelemetry>()?;
+//!     let mut rx = bus.rx::<ExampleTelemetry>()?;
+//!
+//!     // the sender never waits, and the receiver gets the newest samples
+//!     for i in 0..4 {
+//!         tx.send(ExampleTelemetry(i)).await?;
+//!     }
+//!
+//!     assert_eq!(2, rx.recv().await.unwrap().0);
+//!     assert_eq!(3, rx.recv().await.unwrap().0);
+//!     assert_eq!(2, bus.dropped_count::<ExampleTelemetry>());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    counts::TypeCounts,
+    error::{SendError, TryRecvError, TrySendError},
+    intercept::Interceptors,
+    lag::LagTracker,
+    Channel, Receiver, Sender, Storage,
+};
+use async_trait::async_trait;
+use log::debug;
+use std::{
+    any::Any,
+    collections::{BTreeMap, VecDeque},
+    fmt::Debug,
+    marker::PhantomData,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+/// The action a [BackpressureSender](./struct.BackpressureSender.html) takes when the channel is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackpressurePolicy {
+    /// Waits for channel capacity.  `try_send` returns `TrySendError::Full`.
+    #[default]
+    Block,
+    /// Drops the message which is being sent
+    DropNewest,
+    /// Drops the oldest message in the channel, and keeps the message which is being sent.
+    DropOldest,
+    /// Returns the message to the caller, as `SendError::Full(message)`
+    Error,
+    /// Stores the message in an unbounded overflow queue, which the receiver drains in order after the channel
+    Spill,
+}
+
+/// The drop counters of a bus.
+///
+/// The tracker is shared between the bus and all backpressure senders, so the counts include senders which have already been taken.
+#[derive(Clone, Default)]
+pub struct DropTracker {
+    counts: TypeCounts,
+}
+
+impl DropTracker {
+    /// Records a dropped message for the type
+    pub fn record<T: 'static>(&self, policy: BackpressurePolicy) {
+        let name = self.counts.add::<T>(1);
+        debug!("{} dropped by backpressure policy {:?}", name, policy);
+    }
+
+    /// Returns the number of messages of the type which have been dropped
+    pub fn count<T: 'static>(&self) -> u64 {
+        self.counts.get::<T>()
+    }
+
+    /// Returns the number of dropped messages, for each message type name which has dropped messages
+    pub fn counts(&self) -> BTreeMap<String, u64> {
+        self.counts.by_name()
+    }
+}
+
+impl Debug for DropTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DropTracker")
+            .field("counts", &self.counts())
+            .finish()
+    }
+}
+
+/// Messages which did not fit in the channel, shared by the senders and the receiver.
+///
+/// Overflowed messages are always newer than the messages in the channel, so the receiver takes them after the channel is empty.
+/// The senders move them back into the channel when capacity is available.
+#[derive(Default)]
+struct Overflow {
+    capacity: usize,
+    queue: VecDeque<Box<dyn Any + Send>>,
+    /// The number of messages at the front of the channel which have been dropped by `DropOldest`
+    skip: usize,
+}
+
+impl Debug for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Overflow")
+            .field("capacity", &self.capacity)
+            .field("queue", &self.queue.len())
+            .field("skip", &self.skip)
+            .finish()
+    }
+}
+
+type SharedOverflow = Arc<Mutex<Overflow>>;
+
+fn lock(overflow: &SharedOverflow) -> MutexGuard<'_, Overflow> {
+    overflow.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Removes the oldest overflowed message.  The queue only contains messages of the channel type.
+fn pop_front<T: 'static>(overflow: &mut Overflow) -> Option<T> {
+    let value = overflow.queue.pop_front()?;
+    let value = value
+        .downcast::<T>()
+        .expect("overflow queue contains the message type");
+
+    Some(*value)
+}
+
+/// A channel wrapper which applies the backpressure policy configured on the bus.
+/// The Sender of `Chan` is wrapped in [BackpressureSender](./struct.BackpressureSender.html),
+/// and the Receiver is wrapped in [BackpressureReceiver](./struct.BackpressureReceiver.html).
+///
+/// Use it as the channel type in a [Message](../trait.Message.html) implementation to configure a policy for that message.
+/// The `DropOldest` and `Spill` policies share an overflow queue with the receiver, so the channel must implement [SingleConsumer](./trait.SingleConsumer.html).
+/// Broadcast channels already drop the oldest message when a receiver falls behind.
+#[derive(Debug)]
+pub struct Backpressure<Chan> {
+    _chan: PhantomData<fn() -> Chan>,
+}
+
+/// A marker trait for bounded channels which have a single Receiver, such as `mpsc`.
+///
+/// Channels which clone receivers (such as `broadcast`, or the mpmc channels of async-std and flume) would each see a different part of the overflow queue,
+/// so they can't be wrapped in [Backpressure](./struct.Backpressure.html):
+/// ```compile_fail
+/// use lifeline::prelude::*;
+/// use lifeline::backpressure::Backpressure;
+/// use tokio::sync::broadcast;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug, Clone)]
+/// struct ExampleMessage(usize);
+/// impl Message<ExampleBus> for ExampleMessage {
+///     type Channel = Backpressure<broadcast::Sender<Self>>;
+/// }
+///
+/// let bus = ExampleBus::default();
+/// let rx = bus.rx::<ExampleMessage>();
+/// ```
+pub trait SingleConsumer: Channel {}
+
+impl<Chan: SingleConsumer> Channel for Backpressure<Chan> {
+    type Tx = BackpressureSender<Chan::Tx>;
+    type Rx = BackpressureReceiver<Chan::Rx>;
+
+    fn channel(capacity: usize) -> (Self::Tx, Self::Rx) {
+        let (tx, rx) = Chan::channel(capacity);
+
+        let overflow = Arc::new(Mutex::new(Overflow {
+            capacity,
+            ..Overflow::default()
+        }));
+
+        let tx = BackpressureSender {
+            inner: tx,
+            policy: BackpressurePolicy::Block,
+            overflow: overflow.clone(),
+            drops: DropTracker::default(),
+        };
+
+        let rx = BackpressureReceiver {
+            inner: rx,
+            overflow,
+        };
+
+        (tx, rx)
+    }
+
+    fn default_capacity() -> usize {
+        Chan::default_capacity()
+    }
+
+    fn clone_tx(tx: &mut Option<Self::Tx>) -> Option<Self::Tx> {
+        BackpressureSender::map_slot(tx, Chan::clone_tx)
+    }
+
+    fn clone_rx(rx: &mut Option<Self::Rx>, tx: Option<&Self::Tx>) -> Option<Self::Rx> {
+        BackpressureReceiver::map_slot(rx, |inner| {
+            Chan::clone_rx(inner, tx.map(BackpressureSender::get_ref))
+        })
+    }
+
+    fn rx_available(rx: Option<&Self::Rx>, tx: Option<&Self::Tx>) -> bool {
+        Chan::rx_available(
+            rx.map(BackpressureReceiver::get_ref),
+            tx.map(BackpressureSender::get_ref),
+        )
+    }
+
+    fn is_closed(tx: Option<&Self::Tx>, rx: Option<&Self::Rx>) -> bool {
+        Chan::is_closed(
+            tx.map(BackpressureSender::get_ref),
+            rx.map(BackpressureReceiver::get_ref),
+        )
+    }
+
+    fn intercept(tx: &mut Self::Tx, interceptors: &Interceptors) {
+        Chan::intercept(&mut tx.inner, interceptors);
+    }
+
+    fn track_lag(rx: &mut Self::Rx, tracker: &LagTracker) {
+        Chan::track_lag(&mut rx.inner, tracker);
+    }
+
+    fn backpressure(tx: &mut Self::Tx, policy: BackpressurePolicy, drops: &DropTracker) {
+        tx.policy = policy;
+        tx.drops = drops.clone();
+    }
+}
+
+/// A sender wrapper which applies a [BackpressurePolicy](./enum.BackpressurePolicy.html) when the channel is full.
+#[derive(Debug)]
+pub struct BackpressureSender<S> {
+    inner: S,
+    policy: BackpressurePolicy,
+    overflow: SharedOverflow,
+    drops: DropTracker,
+}
+
+impl<S> BackpressureSender<S> {
+    /// Returns a reference to the wrapped sender.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns the backpressure policy of the sender.
+    pub fn policy(&self) -> BackpressurePolicy {
+        self.policy
+    }
+
+    /// Takes the wrapped sender from the option using the provided function, and copies the policy to the result.
+    fn map_slot(
+        slot: &mut Option<Self>,
+        f: impl FnOnce(&mut Option<S>) -> Option<S>,
+    ) -> Option<Self> {
+        let BackpressureSender {
+            inner,
+            policy,
+            overflow,
+            drops,
+        } = slot.take()?;
+
+        let mut inner = Some(inner);
+        let taken = f(&mut inner);
+
+        *slot = inner.map(|inner| BackpressureSender {
+            inner,
+            policy,
+            overflow: overflow.clone(),
+            drops: drops.clone(),
+        });
+
+        taken.map(|inner| BackpressureSender {
+            inner,
+            policy,
+            overflow,
+            drops,
+        })
+    }
+}
+
+impl<S: Storage> Storage for BackpressureSender<S> {
+    fn take_or_clone(res: &mut Option<Self>) -> Option<Self> {
+        Self::map_slot(res, S::take_or_clone)
+    }
+}
+
+impl<S> BackpressureSender<S> {
+    /// Sends the value without waiting, and applies the policy if the channel is full.
+    fn offer<T>(&mut self, value: T) -> Result<(), TrySendError<T>>
+    where
+        T: Debug + Send + 'static,
+        S: Sender<T>,
+    {
+        let mut overflow = lock(&self.overflow);
+
+        // overflowed messages are older than the value, so they are sent first
+        while let Some(front) = pop_front::<T>(&mut overflow) {
+            match self.inner.try_send(front) {
+                Ok(()) => {}
+                Err(err) => {
+                    let closed = matches!(err, TrySendError::Closed(_));
+                    overflow.queue.push_front(Box::new(err.into_inner()));
+
+                    if closed {
+                        return Err(TrySendError::Closed(value));
+                    }
+
+                    break;
+                }
+            }
+        }
+
+        let value = if overflow.queue.is_empty() {
+            match self.inner.try_send(value) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Closed(value)) => return Err(TrySendError::Closed(value)),
+                Err(TrySendError::Full(value)) => value,
+            }
+        } else {
+            value
+        };
+
+        match self.policy {
+            BackpressurePolicy::Block | BackpressurePolicy::Error => Err(TrySendError::Full(value)),
+            BackpressurePolicy::DropNewest => {
+                self.drops.record::<T>(self.policy);
+                Ok(())
+            }
+            BackpressurePolicy::DropOldest => {
+                overflow.queue.push_back(Box::new(value));
+
+                // drop the oldest message in the channel, or in the overflow if the channel messages are already dropped
+                if overflow.skip < overflow.capacity {
+                    overflow.skip += 1;
+                } else {
+                    overflow.queue.pop_front();
+                }
+
+                self.drops.record::<T>(self.policy);
+                Ok(())
+            }
+            BackpressurePolicy::Spill => {
+                overflow.queue.push_back(Box::new(value));
+                Ok(())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T, S> Sender<T> for BackpressureSender<S>
+where
+    T: Debug + Send + 'static,
+    S: Sender<T> + Send,
+{
+    async fn send(&mut self, value: T) -> Result<(), SendError<T>> {
+        match self.policy {
+            BackpressurePolicy::Block => self.inner.send(value).await,
+            _ => self.offer(value).map_err(|err| match err {
+                TrySendError::Full(value) => SendError::Full(value),
+                TrySendError::Closed(value) => SendError::Return(value),
+            }),
+        }
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        match self.policy {
+            BackpressurePolicy::Block => self.inner.try_send(value),
+            _ => self.offer(value),
+        }
+    }
+
+    async fn send_timeout(&mut self, value: T, timeout: Duration) -> Result<(), SendError<T>> {
+        match self.policy {
+            BackpressurePolicy::Block => self.inner.send_timeout(value, timeout).await,
+            // the other policies never wait for capacity
+            _ => self.send(value).await,
+        }
+    }
+}
+
+/// A receiver wrapper which receives the messages of a [BackpressureSender](./struct.BackpressureSender.html), including overflowed messages.
+#[derive(Debug)]
+pub struct BackpressureReceiver<R> {
+    inner: R,
+    overflow: SharedOverflow,
+}
+
+impl<R> BackpressureReceiver<R> {
+    /// Returns a reference to the wrapped receiver.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Takes the wrapped receiver from the option using the provided function, and shares the overflow queue with the result.
+    fn map_slot(
+        slot: &mut Option<Self>,
+        f: impl FnOnce(&mut Option<R>) -> Option<R>,
+    ) -> Option<Self> {
+        let BackpressureReceiver { inner, overflow } = slot.take()?;
+
+        let mut inner = Some(inner);
+        let taken = f(&mut inner);
+
+        *slot = inner.map(|inner| BackpressureReceiver {
+            inner,
+            overflow: overflow.clone(),
+        });
+
+        taken.map(|inner| BackpressureReceiver { inner, overflow })
+    }
+
+    /// Receives a message without waiting, from the channel or the overflow queue.
+    fn take<T>(&mut self) -> Result<T, TryRecvError>
+    where
+        T: Send + 'static,
+        R: Receiver<T>,
+    {
+        let mut overflow = lock(&self.overflow);
+
+        while overflow.skip > 0 {
+            match self.inner.try_recv() {
+                Ok(_dropped) => overflow.skip -= 1,
+                Err(_) => overflow.skip = 0,
+            }
+        }
+
+        match self.inner.try_recv() {
+            Ok(value) => Ok(value),
+            Err(err) => pop_front(&mut overflow).ok_or(err),
+        }
+    }
+
+    /// Returns true if the received message was dropped by `DropOldest`
+    fn skip(&self) -> bool {
+        let mut overflow = lock(&self.overflow);
+
+        if overflow.skip > 0 {
+            overflow.skip -= 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<R: Storage> Storage for BackpressureReceiver<R> {
+    fn take_or_clone(res: &mut Option<Self>) -> Option<Self> {
+        Self::map_slot(res, R::take_or_clone)
+    }
+}
+
+#[async_trait]
+impl<T, R> Receiver<T> for BackpressureReceiver<R>
+where
+    T: Send + 'static,
+    R: Receiver<T> + Send,
+{
+    async fn recv(&mut self) -> Option<T> {
+        loop {
+            match self.take() {
+                Ok(value) => return Some(value),
+                Err(TryRecvError::Closed) => return None,
+                Err(TryRecvError::Empty) => {}
+            }
+
+            // the overflow is empty, so the senders write to the channel
+            match self.inner.recv().await {
+                Some(_dropped) if self.skip() => continue,
+                Some(value) => return Some(value),
+                None => return self.take().ok(),
+            }
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.take()
+    }
+}
+
+#[cfg(all(test, feature = "tokio-channels"))]
+mod tests {
+    use super::{Backpressure, BackpressurePolicy};
+    use crate::{
+        dyn_bus::DynBus,
+        error::{SendError, TryRecvError, TrySendError},
+        lifeline_bus, lifeline_static_bus,
+        static_bus::StaticBus,
+        Bus, Message, Receiver, Sender,
+    };
+    use tokio::sync::mpsc;
+
+    lifeline_bus!(struct PolicyBus);
+
+    #[derive(Debug, PartialEq)]
+    struct PolicyMessage(usize);
+
+    impl Message<PolicyBus> for PolicyMessage {
+        type Channel = Backpressure<mpsc::Sender<Self>>;
+    }
+
+    /// Sends the values on a channel with capacity 2, and returns the send results
+    async fn run(bus: &PolicyBus, values: usize) -> Vec<Result<(), SendError<PolicyMessage>>> {
+        bus.capacity::<PolicyMessage>(2).unwrap();
+
+        let mut tx = bus.tx::<PolicyMessage>().unwrap();
+        let mut results = Vec::new();
+        for i in 0..values {
+            results.push(tx.send(PolicyMessage(i)).await);
+        }
+
+        results
+    }
+
+    async fn received(bus: &PolicyBus) -> Vec<usize> {
+        let mut rx = bus.rx::<PolicyMessage>().unwrap();
+        let mut received = Vec::new();
+        while let Ok(PolicyMessage(i)) = rx.try_recv() {
+            received.push(i);
+        }
+
+        received
+    }
+
+    #[tokio::test]
+    async fn spill_preserves_order() {
+        let bus = PolicyBus::default();
+        bus.backpressure::<PolicyMessage>(BackpressurePolicy::Spill)
+            .unwrap();
+
+        let results = run(&bus, 5).await;
+        assert!(results.iter().all(Result::is_ok));
+
+        assert_eq!(vec![0, 1, 2, 3, 4], received(&bus).await);
+        assert_eq!(0, bus.dropped_count::<PolicyMessage>());
+    }
+
+    #[tokio::test]
+    async fn drop_newest_keeps_buffered() {
+        let bus = PolicyBus::default();
+        bus.backpressure::<PolicyMessage>(BackpressurePolicy::DropNewest)
+            .unwrap();
+
+        run(&bus, 4).await;
+
+        assert_eq!(vec![0, 1], received(&bus).await);
+        assert_eq!(2, bus.dropped_count::<PolicyMessage>());
+    }
+
+    #[tokio::test]
+    async fn error_returns_message() {
+        let bus = PolicyBus::default();
+        bus.backpressure::<PolicyMessage>(BackpressurePolicy::Error)
+            .unwrap();
+
+        let results = run(&bus, 3).await;
+        assert_eq!(Err(SendError::Full(PolicyMessage(2))), results[2]);
+
+        assert_eq!(vec![0, 1], received(&bus).await);
+        assert_eq!(0, bus.dropped_count::<PolicyMessage>());
+    }
+
+    #[test]
+    fn policy_after_link_is_rejected() {
+        let bus = PolicyBus::default();
+        let _tx = bus.tx::<PolicyMessage>().unwrap();
+
+        assert!(bus
+            .backpressure::<PolicyMessage>(BackpressurePolicy::DropOldest)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn block_try_send_returns_full() {
+        let bus = PolicyBus::default();
+        bus.capacity::<PolicyMessage>(2).unwrap();
+
+        let mut tx = bus.tx::<PolicyMessage>().unwrap();
+        tx.try_send(PolicyMessage(0)).unwrap();
+        tx.try_send(PolicyMessage(1)).unwrap();
+
+        assert_eq!(
+            Err(TrySendError::Full(PolicyMessage(2))),
+            tx.try_send(PolicyMessage(2))
+        );
+        assert_eq!(vec![0, 1], received(&bus).await);
+        assert_eq!(0, bus.dropped_count::<PolicyMessage>());
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_keeps_newest() {
+        let bus = PolicyBus::default();
+        bus.backpressure::<PolicyMessage>(BackpressurePolicy::DropOldest)
+            .unwrap();
+
+        // more than twice the capacity, so messages are dropped from the channel and the overflow
+        let results = run(&bus, 7).await;
+        assert!(results.iter().all(Result::is_ok));
+
+        assert_eq!(vec![5, 6], received(&bus).await);
+        assert_eq!(5, bus.dropped_count::<PolicyMessage>());
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_with_interleaved_recv() {
+        let bus = PolicyBus::default();
+        bus.backpressure::<PolicyMessage>(BackpressurePolicy::DropOldest)
+            .unwrap();
+        run(&bus, 4).await;
+
+        let mut tx = bus.tx::<PolicyMessage>().unwrap();
+        let mut rx = bus.rx::<PolicyMessage>().unwrap();
+
+        assert_eq!(Some(PolicyMessage(2)), rx.recv().await);
+
+        tx.send(PolicyMessage(4)).await.unwrap();
+        tx.send(PolicyMessage(5)).await.unwrap();
+
+        assert_eq!(Some(PolicyMessage(4)), rx.recv().await);
+        assert_eq!(Some(PolicyMessage(5)), rx.recv().await);
+        assert_eq!(Err(TryRecvError::Empty), rx.try_recv());
+        assert_eq!(3, bus.dropped_count::<PolicyMessage>());
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_with_cloned_senders() {
+        let bus = PolicyBus::default();
+        bus.capacity::<PolicyMessage>(2).unwrap();
+        bus.backpressure::<PolicyMessage>(BackpressurePolicy::DropOldest)
+            .unwrap();
+
+        let mut tx_1 = bus.tx::<PolicyMessage>().unwrap();
+        let mut tx_2 = bus.tx::<PolicyMessage>().unwrap();
+
+        for i in (0..6).step_by(2) {
+            tx_1.send(PolicyMessage(i)).await.unwrap();
+            tx_2.send(PolicyMessage(i + 1)).await.unwrap();
+        }
+
+        assert_eq!(vec![4, 5], received(&bus).await);
+        assert_eq!(4, bus.dropped_count::<PolicyMessage>());
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_with_closed_receiver() {
+        let bus = PolicyBus::default();
+        bus.capacity::<PolicyMessage>(2).unwrap();
+        bus.backpressure::<PolicyMessage>(BackpressurePolicy::DropOldest)
+            .unwrap();
+
+        let mut tx = bus.tx::<PolicyMessage>().unwrap();
+        for i in 0..3 {
+            tx.send(PolicyMessage(i)).await.unwrap();
+        }
+
+        drop(bus.rx::<PolicyMessage>().unwrap());
+
+        assert_eq!(
+            Err(SendError::Return(PolicyMessage(3))),
+            tx.send(PolicyMessage(3)).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(PolicyMessage(4))),
+            tx.try_send(PolicyMessage(4))
+        );
+        assert_eq!(1, bus.dropped_count::<PolicyMessage>());
+    }
+
+    #[tokio::test]
+    async fn spill_with_waiting_receiver() {
+        let bus = PolicyBus::default();
+        bus.capacity::<PolicyMessage>(2).unwrap();
+        bus.backpressure::<PolicyMessage>(BackpressurePolicy::Spill)
+            .unwrap();
+
+        let mut tx = bus.tx::<PolicyMessage>().unwrap();
+        let mut rx = bus.rx::<PolicyMessage>().unwrap();
+
+        let receiver = tokio::spawn(async move {
+            let mut received = Vec::new();
+            while let Some(PolicyMessage(i)) = rx.recv().await {
+                received.push(i);
+            }
+
+            received
+        });
+
+        // the receiver is waiting on the channel when the messages spill
+        tokio::task::yield_now().await;
+        for i in 0..5 {
+            tx.send(PolicyMessage(i)).await.unwrap();
+        }
+
+        drop(tx);
+        drop(bus);
+
+        assert_eq!(vec![0, 1, 2, 3, 4], receiver.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn keyed_channels_have_their_own_policy() {
+        let bus = PolicyBus::default();
+        bus.capacity_keyed::<PolicyMessage, _>("drop", 2).unwrap();
+        bus.backpressure_keyed::<PolicyMessage, _>("drop", BackpressurePolicy::DropNewest)
+            .unwrap();
+
+        let mut tx = bus.tx_keyed::<PolicyMessage, _>("drop").unwrap();
+        for i in 0..3 {
+            tx.send(PolicyMessage(i)).await.unwrap();
+        }
+
+        // the unkeyed channel keeps the default policy
+        let results = run(&bus, 2).await;
+        assert!(results.iter().all(Result::is_ok));
+
+        let mut rx = bus.rx_keyed::<PolicyMessage, _>("drop").unwrap();
+        assert_eq!(Ok(PolicyMessage(0)), rx.try_recv());
+        assert_eq!(Ok(PolicyMessage(1)), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Empty), rx.try_recv());
+        assert_eq!(1, bus.dropped_count::<PolicyMessage>());
+
+        assert!(bus
+            .backpressure_keyed::<PolicyMessage, _>("drop", BackpressurePolicy::Spill)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn static_bus_counts_drops() {
+        lifeline_static_bus!(
+            struct StaticPolicyBus {
+                channels {
+                    message: PolicyMessage => Backpressure<mpsc::Sender<Self>>,
+                }
+            }
+        );
+
+        let bus = StaticPolicyBus::default();
+        bus.capacity::<PolicyMessage>(1).unwrap();
+        bus.backpressure::<PolicyMessage>(BackpressurePolicy::DropNewest)
+            .unwrap();
+
+        let mut tx = bus.tx::<PolicyMessage>().unwrap();
+        for i in 0..3 {
+            tx.send(PolicyMessage(i)).await.unwrap();
+        }
+
+        assert_eq!(2, bus.dropped_count::<PolicyMessage>());
+    }
+}
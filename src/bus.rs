@@ -1,8 +1,11 @@
 use crate::{
-    error::{AlreadyLinkedError, TakeChannelError, TakeResourceError},
-    Channel, Storage,
+    backpressure::BackpressurePolicy,
+    error::{type_name, AlreadyLinkedError, TakeChannelError, TakeResourceError},
+    static_bus::{ChannelSlot, ResourceSlot},
+    BoxReceiver, BoxSender, Channel, Receiver, Sender, Storage,
 };
 
+use log::{debug, warn};
 use std::fmt::{Debug, Display};
 
 /// Attaches a channel to the [Bus](./trait.Bus.html), carrying `Self` as a message.
@@ -31,8 +34,29 @@ use std::fmt::{Debug, Display};
 ///     Ok(())
 /// }
 /// ```
-pub trait Message<Bus>: Debug {
+pub trait Message<Bus>: Debug + __private::MessageBinding<Bus> {
     type Channel: Channel;
+
+    /// Returns the typed slot which stores this channel on a static bus.
+    /// This is implemented by the [lifeline_static_bus!](./macro.lifeline_static_bus.html) macro, and is not used by dynamic busses.
+    #[doc(hidden)]
+    fn static_slot(_bus: &Bus) -> Option<&ChannelSlot<Self::Channel>> {
+        None
+    }
+}
+
+/// Binding traits, which are implemented by lifeline and its macros.  This module is not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    /// Binds a message type to a bus.  Implemented for all message types on dynamic busses,
+    /// and by the [lifeline_static_bus!](../macro.lifeline_static_bus.html) macro for each message bound on a static bus.
+    ///
+    /// This seals `Message` for static busses.  A `Message` implementation which was not generated by the macro would have no slot on the bus, so it doesn't compile.
+    pub trait MessageBinding<Bus> {}
+
+    /// Binds a resource type to a bus.  Implemented for all resource types on dynamic busses,
+    /// and by the [lifeline_static_bus!](../macro.lifeline_static_bus.html) macro for each resource bound on a static bus.
+    pub trait ResourceBinding<Bus> {}
 }
 
 /// Attaches a resource to the [Bus](./trait.Bus.html).  This resource can accessed from the bus using [bus.resource::\<Self\>()](trait.Bus.html#tymethod.resource).
@@ -55,13 +79,21 @@ pub trait Message<Bus>: Debug {
 ///
 /// impl Resource<ExampleBus> for MyResource {}
 /// ```
-pub trait Resource<Bus>: Storage + Debug + Send {}
+pub trait Resource<Bus>: Storage + Debug + Send + __private::ResourceBinding<Bus> {
+    /// Returns the typed slot which stores this resource on a static bus.
+    /// This is implemented by the [lifeline_static_bus!](./macro.lifeline_static_bus.html) macro, and is not used by dynamic busses.
+    #[doc(hidden)]
+    fn static_slot(_bus: &Bus) -> Option<&ResourceSlot<Self>> {
+        None
+    }
+}
 
 /// Stores and distributes channel endpoints ([Senders](./trait.Sender.html) and [Receivers](./trait.Receiver.html)), as well as [Resource](./trait.Resource.html) values.
 ///
 /// The bus allows you to write loosely-coupled applications, with adjacent lifeline [Services](./trait.Service.html) that do not depend on each other.
 ///
 /// Most Bus implementations are defined using the [lifeline_bus!](./macro.lifeline_bus.html) macro.
+/// Busses which carry a fixed set of messages can be defined with the [lifeline_static_bus!](./macro.lifeline_static_bus.html) macro.
 ///
 /// ## Example:
 /// ```
@@ -72,6 +104,8 @@ pub trait Resource<Bus>: Storage + Debug + Send {}
 pub trait Bus: Default + Debug + Sized {
     /// Configures the channel capacity, if the linked channel implementation takes a capacity during initialization
     ///
+    /// Unbounded channels (such as `mpsc::UnboundedSender`) ignore the capacity.
+    ///
     /// Returns an [AlreadyLinkedError](./error/struct.AlreadyLinkedError.html), if the channel has already been initalized from another call to `capacity`, `rx`, or `tx`.
     ///
     /// ## Example:
@@ -95,11 +129,36 @@ pub trait Bus: Default + Debug + Sized {
     where
         Msg: Message<Self> + 'static;
 
+    /// Configures the backpressure policy, which decides what happens to a message when the channel is full.
+    ///
+    /// The policy is applied if the channel type is wrapped in [Backpressure](./backpressure/struct.Backpressure.html).
+    /// Other channels keep the behavior of the backend, and log a warning if the policy is not `BackpressurePolicy::Block`.
+    /// See the [backpressure](./backpressure/index.html) module for an example.
+    ///
+    /// Returns an [AlreadyLinkedError](./error/struct.AlreadyLinkedError.html), if the policy has already been configured, or if the channel has already been linked.
+    ///
+    /// The default implementation does not support policies, and logs a warning if the policy is not `BackpressurePolicy::Block`.
+    fn backpressure<Msg>(&self, policy: BackpressurePolicy) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<Self> + 'static,
+    {
+        if policy != BackpressurePolicy::Block {
+            warn!(
+                "{} does not support backpressure policies, {:?} is ignored for {}",
+                type_name::<Self>(),
+                policy,
+                type_name::<Msg>()
+            );
+        }
+
+        Ok(())
+    }
+
     /// Takes (or clones) the channel [Receiver](./trait.Receiver.html).  The message type must implement [Message\<Bus\>](./trait.Message.html), which defines the channel type.
     ///
     /// Returns the [Receiver](./trait.Receiver.html), or a [TakeChannelError](./error/enum.TakeChannelError.html) if the channel endpoint is not clonable, and has already been taken.
     ///
-    /// - For `mpsc` channels, the Receiver is taken.
+    /// - For `mpsc` and unbounded `mpsc` channels, the Receiver is taken.
     /// - For `broadcast` channels, the Receiver is cloned.
     /// - For `watch` channels, the Receiver is cloned.
     ///
@@ -128,7 +187,7 @@ pub trait Bus: Default + Debug + Sized {
     ///
     /// Returns the sender, or a [TakeChannelError](./error/enum.TakeChannelError.html) if the channel endpoint is not clonable, and has already been taken.
     ///
-    /// - For `mpsc` channels, the Sender is cloned.
+    /// - For `mpsc` and unbounded `mpsc` channels, the Sender is cloned.
     /// - For `broadcast` channels, the Sender is cloned.
     /// - For `watch` channels, the Sender is taken.
     ///
@@ -177,6 +236,215 @@ pub trait Bus: Default + Debug + Sized {
     fn resource<Res>(&self) -> Result<Res, TakeResourceError>
     where
         Res: Resource<Self>;
+
+    /// Links a fresh channel for the message type, replacing the existing channel if it has already been linked.
+    ///
+    /// The endpoints stored on the bus are dropped, and future calls to `rx` and `tx` return endpoints for the new channel.
+    /// Endpoints which were already taken remain connected to the old channel.  Once the old receivers are dropped, the old senders are closed.
+    ///
+    /// This is useful when a service owning a non-clonable endpoint (such as an `mpsc::Receiver`) is restarted.
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::prelude::*;
+    /// use tokio::sync::mpsc;
+    /// lifeline_bus!(pub struct ExampleBus);
+    ///
+    /// #[derive(Debug)]
+    /// struct ExampleMessage {}
+    /// impl Message<ExampleBus> for ExampleMessage {
+    ///     type Channel = mpsc::Sender<Self>;
+    /// }
+    ///
+    /// fn main() {
+    ///     let bus = ExampleBus::default();
+    ///     let rx = bus.rx::<ExampleMessage>();
+    ///     assert!(rx.is_ok());
+    ///     assert!(bus.rx::<ExampleMessage>().is_err());
+    ///
+    ///     bus.relink::<ExampleMessage>();
+    ///     assert!(bus.rx::<ExampleMessage>().is_ok());
+    /// }
+    /// ```
+    ///
+    /// The default implementation does not support relinking, and logs a warning.
+    fn relink<Msg>(&self)
+    where
+        Msg: Message<Self> + 'static,
+    {
+        warn!(
+            "{} does not support relinking, {} is not relinked",
+            type_name::<Self>(),
+            type_name::<Msg>()
+        );
+    }
+
+    /// Returns true if the channel for the message type has been linked (by `rx`, `tx`, or a stored endpoint).
+    ///
+    /// This does not link the channel.  Configuring the [capacity](#tymethod.capacity) does not link the channel either,
+    /// as the capacity is used when the channel is linked.
+    ///
+    /// The default implementation returns false.
+    fn is_linked<Msg>(&self) -> bool
+    where
+        Msg: Message<Self> + 'static,
+    {
+        false
+    }
+
+    /// Returns true if a call to [bus.capacity::\<Msg\>()](#tymethod.capacity) would configure the capacity.
+    ///
+    /// Returns false if the channel has been linked, or if the capacity has already been configured.
+    /// The default implementation only checks whether the channel has been linked.
+    fn capacity_available<Msg>(&self) -> bool
+    where
+        Msg: Message<Self> + 'static,
+    {
+        !self.is_linked::<Msg>()
+    }
+
+    /// Returns true if a call to [bus.rx::\<Msg\>()](#tymethod.rx) would return a [Receiver](./trait.Receiver.html).
+    ///
+    /// Unlike `rx`, this does not link the channel or take the receiver.  It can be used to check whether another service has already consumed a stream.
+    /// If the channel is not linked yet, the receiver is available.
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::prelude::*;
+    /// use tokio::sync::mpsc;
+    /// lifeline_bus!(pub struct ExampleBus);
+    ///
+    /// #[derive(Debug)]
+    /// struct ExampleMessage {}
+    /// impl Message<ExampleBus> for ExampleMessage {
+    ///     type Channel = mpsc::Sender<Self>;
+    /// }
+    ///
+    /// fn main() {
+    ///     let bus = ExampleBus::default();
+    ///     assert!(bus.rx_available::<ExampleMessage>());
+    ///     assert!(!bus.is_linked::<ExampleMessage>());
+    ///
+    ///     let rx = bus.rx::<ExampleMessage>();
+    ///     assert!(bus.is_linked::<ExampleMessage>());
+    ///     assert!(!bus.rx_available::<ExampleMessage>());
+    ///     assert!(bus.tx_available::<ExampleMessage>());
+    /// }
+    /// ```
+    ///
+    /// The default implementation returns true, and `rx` returns an error if the receiver is not available.
+    fn rx_available<Msg>(&self) -> bool
+    where
+        Msg: Message<Self> + 'static,
+    {
+        true
+    }
+
+    /// Returns true if a call to [bus.tx::\<Msg\>()](#tymethod.tx) would return a [Sender](./trait.Sender.html).
+    ///
+    /// Unlike `tx`, this does not link the channel or take the sender.  If the channel is not linked yet, the sender is available.
+    ///
+    /// The default implementation returns true, and `tx` returns an error if the sender is not available.
+    fn tx_available<Msg>(&self) -> bool
+    where
+        Msg: Message<Self> + 'static,
+    {
+        true
+    }
+
+    /// Returns true if a call to [bus.resource::\<Res\>()](#tymethod.resource) would return the resource.
+    ///
+    /// Unlike `resource`, this does not take the resource.  Returns false if the resource has not been stored, or has been taken.
+    ///
+    /// The default implementation returns true, and `resource` returns an error if the resource is not available.
+    fn resource_available<Res>(&self) -> bool
+    where
+        Res: Resource<Self>,
+    {
+        true
+    }
+
+    /// Takes the channel [Receiver](./trait.Receiver.html) from the bus, and returns all the messages which are buffered in the channel.
+    ///
+    /// This is useful during shutdown, to persist or log messages which were never processed.
+    /// Only the receiver stored on the bus can be drained.  If the receiver has been taken by a service (or the channel is not linked), returns an empty Vec.
+    /// For broadcast channels, the stored receiver holds all messages sent since the channel was linked, until it is first taken.
+    ///
+    /// To record the number of undelivered messages for several message types, use a [DrainReport](./struct.DrainReport.html).
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::prelude::*;
+    /// use tokio::sync::mpsc;
+    /// lifeline_bus!(pub struct ExampleBus);
+    ///
+    /// #[derive(Debug)]
+    /// struct ExampleMessage {}
+    /// impl Message<ExampleBus> for ExampleMessage {
+    ///     type Channel = mpsc::Sender<Self>;
+    /// }
+    ///
+    /// fn main() {
+    ///     let bus = ExampleBus::default();
+    ///     let tx = bus.tx::<ExampleMessage>().unwrap();
+    ///     tx.try_send(ExampleMessage {}).unwrap();
+    ///     tx.try_send(ExampleMessage {}).unwrap();
+    ///
+    ///     let undelivered = bus.drain::<ExampleMessage>();
+    ///     assert_eq!(2, undelivered.len());
+    /// }
+    /// ```
+    fn drain<Msg>(&self) -> Vec<Msg>
+    where
+        Msg: Message<Self> + Send + 'static,
+        <Msg::Channel as Channel>::Rx: Receiver<Msg>,
+    {
+        if !self.is_linked::<Msg>() || !self.rx_available::<Msg>() {
+            return Vec::new();
+        }
+
+        let mut rx = match self.rx::<Msg>() {
+            Ok(rx) => rx,
+            Err(_) => return Vec::new(),
+        };
+
+        // try_recv is not limited by the executor's cooperative budget, so large queues are fully drained
+        let mut messages = Vec::new();
+        while let Ok(message) = rx.try_recv() {
+            messages.push(message);
+        }
+
+        debug!(
+            "{} drained from {}: {} messages",
+            type_name::<Msg>(),
+            type_name::<Self>(),
+            messages.len()
+        );
+
+        messages
+    }
+
+    /// Takes (or clones) the channel [Sender](./trait.Sender.html), and boxes it as a [BoxSender](./struct.BoxSender.html).
+    ///
+    /// Boxed senders don't depend on the channel backend, so services which take them can be used with any bus which carries the message.
+    fn tx_boxed<Msg>(&self) -> Result<BoxSender<Msg>, TakeChannelError>
+    where
+        Msg: Message<Self> + Send + 'static,
+        <Msg::Channel as Channel>::Tx: Sender<Msg>,
+    {
+        self.tx::<Msg>().map(BoxSender::new)
+    }
+
+    /// Takes (or clones) the channel [Receiver](./trait.Receiver.html), and boxes it as a [BoxReceiver](./struct.BoxReceiver.html).
+    ///
+    /// Boxed receivers don't depend on the channel backend, so services which take them can be used with any bus which carries the message.
+    fn rx_boxed<Msg>(&self) -> Result<BoxReceiver<Msg>, TakeChannelError>
+    where
+        Msg: Message<Self> + Send + 'static,
+        <Msg::Channel as Channel>::Rx: Receiver<Msg>,
+    {
+        self.rx::<Msg>().map(BoxReceiver::new)
+    }
 }
 
 /// Represents the Sender, Receiver, or Both.  Used in error types.
@@ -1,16 +1,32 @@
-use crate::Storage;
+use crate::{
+    backpressure::{BackpressurePolicy, DropTracker},
+    error::type_name,
+    intercept::Interceptors,
+    lag::LagTracker,
+    Storage,
+};
+use log::warn;
 
 #[cfg(feature = "async-std-channels")]
 mod async_std;
 
+pub(crate) mod adapter;
+pub(crate) mod boxed;
+pub(crate) mod coalesce;
 pub mod lifeline;
+pub(crate) mod priority;
+mod shared;
+pub(crate) mod stream;
 
 #[cfg(feature = "tokio-channels")]
-mod tokio;
+pub(crate) mod tokio;
 
 #[cfg(feature = "postage-channels")]
 mod postage;
 
+#[cfg(feature = "flume-channels")]
+pub(crate) mod flume;
+
 /// A channel's (Sender, Receiver) pair.  Defines how the bus constructs and retrieves the values.
 ///
 /// Channel endpoints can either be taken, or cloned.  The `Channel` trait has default implementations that honor the
@@ -39,4 +55,48 @@ pub trait Channel {
     fn clone_rx(rx: &mut Option<Self::Rx>, _tx: Option<&Self::Tx>) -> Option<Self::Rx> {
         Self::Rx::take_or_clone(rx)
     }
+
+    /// Returns true if a Receiver could be taken (or cloned) from the endpoints which are still stored on the bus.
+    ///
+    /// The default implementation returns true if the Receiver is stored.  Channels which create receivers from the
+    /// sender (such as broadcast channels) should also return true if the Sender is stored.
+    fn rx_available(rx: Option<&Self::Rx>, _tx: Option<&Self::Tx>) -> bool {
+        rx.is_some()
+    }
+
+    /// Returns true if the channel has been closed, and can no longer deliver messages.
+    /// The endpoints which are still stored on the bus are provided (if they haven't been taken).
+    ///
+    /// This is used to relink channels automatically, if auto-relink is enabled on the bus.
+    /// The default implementation returns false, which means the channel is never relinked automatically.
+    fn is_closed(_tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        false
+    }
+
+    /// Attaches the bus interceptors to a Sender which was linked by the bus.
+    ///
+    /// This is implemented by [InterceptSender](./intercept/struct.InterceptSender.html) and [PrioritySender](./struct.PrioritySender.html),
+    /// which run the interceptors on each message, in the sending task.
+    /// The default implementation does nothing, and messages on the channel are not intercepted.
+    fn intercept(_tx: &mut Self::Tx, _interceptors: &Interceptors) {}
+
+    /// Attaches the bus lag tracker to a Receiver which was taken (or cloned) from the bus.
+    ///
+    /// This is implemented by [TrackLag](./lag/struct.TrackLag.html), which counts lagged messages and applies the bus lag policy.
+    /// The default implementation does nothing, and lag on the channel is not tracked.
+    fn track_lag(_rx: &mut Self::Rx, _tracker: &LagTracker) {}
+
+    /// Applies the backpressure policy configured on the bus to a Sender which was linked by the bus.
+    ///
+    /// This is implemented by [Backpressure](./backpressure/struct.Backpressure.html), which applies the policy when the channel is full.
+    /// The default implementation logs a warning if the policy is not `BackpressurePolicy::Block`, and the channel keeps its own behavior.
+    fn backpressure(_tx: &mut Self::Tx, policy: BackpressurePolicy, _drops: &DropTracker) {
+        if policy != BackpressurePolicy::Block {
+            warn!(
+                "{} does not support backpressure policies, {:?} is ignored",
+                type_name::<Self>(),
+                policy
+            );
+        }
+    }
 }
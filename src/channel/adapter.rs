@@ -0,0 +1,353 @@
+use crate::{
+    error::{SendError, TryRecvError, TrySendError},
+    Receiver, Recv, Sender,
+};
+use async_trait::async_trait;
+use std::{fmt::Debug, marker::PhantomData, time::Duration};
+
+/// A receiver which converts each message with a function.  Created by [Receiver::map_recv](./trait.Receiver.html#method.map_recv).
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use tokio::sync::mpsc;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// struct ExampleTemperature {
+///     celsius: f64,
+/// }
+///
+/// impl Message<ExampleBus> for ExampleTemperature {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// // the service only depends on the converted message
+/// async fn record(mut rx: impl lifeline::Receiver<f64>) -> Option<f64> {
+///     rx.recv().await
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     use lifeline::Receiver;
+///
+///     let bus = ExampleBus::default();
+///     let tx = bus.tx::<ExampleTemperature>()?;
+///     let rx = bus.rx::<ExampleTemperature>()?;
+///
+///     tx.send(ExampleTemperature { celsius: -40.0 }).await?;
+///     tx.send(ExampleTemperature { celsius: 100.0 }).await?;
+///
+///     let fahrenheit = rx
+///         .filter_recv(|temp| temp.celsius > 0.0)
+///         .map_recv(|temp| temp.celsius * 9.0 / 5.0 + 32.0);
+///
+///     assert_eq!(Some(212.0), record(fahrenheit).await);
+///     Ok(())
+/// }
+/// ```
+pub struct MapReceiver<R, F, T> {
+    inner: R,
+    f: F,
+    _t: PhantomData<fn(T)>,
+}
+
+impl<R, F, T> MapReceiver<R, F, T> {
+    pub(crate) fn new(inner: R, f: F) -> Self {
+        Self {
+            inner,
+            f,
+            _t: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped receiver
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<R, F, T, U> Receiver<U> for MapReceiver<R, F, T>
+where
+    R: Receiver<T> + Send,
+    F: FnMut(T) -> U + Send,
+    T: Send + 'static,
+    U: Send,
+{
+    async fn recv(&mut self) -> Option<U> {
+        self.inner.recv().await.map(&mut self.f)
+    }
+
+    fn try_recv(&mut self) -> Result<U, TryRecvError> {
+        self.inner.try_recv().map(&mut self.f)
+    }
+
+    async fn recv_lossy(&mut self) -> Recv<U> {
+        match self.inner.recv_lossy().await {
+            Recv::Value(value) => Recv::Value((self.f)(value)),
+            Recv::Lagged(lagged) => Recv::Lagged(lagged),
+            Recv::Closed => Recv::Closed,
+        }
+    }
+}
+
+impl<R: Debug, F, T> Debug for MapReceiver<R, F, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MapReceiver")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+/// A receiver which skips messages which don't match a predicate.  Created by [Receiver::filter_recv](./trait.Receiver.html#method.filter_recv).
+pub struct FilterReceiver<R, F> {
+    inner: R,
+    f: F,
+}
+
+impl<R, F> FilterReceiver<R, F> {
+    pub(crate) fn new(inner: R, f: F) -> Self {
+        Self { inner, f }
+    }
+
+    /// Returns the wrapped receiver
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<R, F, T> Receiver<T> for FilterReceiver<R, F>
+where
+    R: Receiver<T> + Send,
+    F: FnMut(&T) -> bool + Send,
+    T: Send + 'static,
+{
+    async fn recv(&mut self) -> Option<T> {
+        loop {
+            let value = self.inner.recv().await?;
+            if (self.f)(&value) {
+                return Some(value);
+            }
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        loop {
+            let value = self.inner.try_recv()?;
+            if (self.f)(&value) {
+                return Ok(value);
+            }
+        }
+    }
+
+    async fn recv_lossy(&mut self) -> Recv<T> {
+        loop {
+            match self.inner.recv_lossy().await {
+                Recv::Value(value) if !(self.f)(&value) => continue,
+                received => return received,
+            }
+        }
+    }
+}
+
+impl<R: Debug, F> Debug for FilterReceiver<R, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FilterReceiver")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+/// A receiver which converts each message with a function, and skips messages which convert to `None`.
+/// Created by [Receiver::filter_map_recv](./trait.Receiver.html#method.filter_map_recv).
+pub struct FilterMapReceiver<R, F, T> {
+    inner: R,
+    f: F,
+    _t: PhantomData<fn(T)>,
+}
+
+impl<R, F, T> FilterMapReceiver<R, F, T> {
+    pub(crate) fn new(inner: R, f: F) -> Self {
+        Self {
+            inner,
+            f,
+            _t: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped receiver
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<R, F, T, U> Receiver<U> for FilterMapReceiver<R, F, T>
+where
+    R: Receiver<T> + Send,
+    F: FnMut(T) -> Option<U> + Send,
+    T: Send + 'static,
+    U: Send,
+{
+    async fn recv(&mut self) -> Option<U> {
+        loop {
+            let value = self.inner.recv().await?;
+            if let Some(value) = (self.f)(value) {
+                return Some(value);
+            }
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<U, TryRecvError> {
+        loop {
+            let value = self.inner.try_recv()?;
+            if let Some(value) = (self.f)(value) {
+                return Ok(value);
+            }
+        }
+    }
+
+    async fn recv_lossy(&mut self) -> Recv<U> {
+        loop {
+            match self.inner.recv_lossy().await {
+                Recv::Value(value) => match (self.f)(value) {
+                    Some(value) => return Recv::Value(value),
+                    None => continue,
+                },
+                Recv::Lagged(lagged) => return Recv::Lagged(lagged),
+                Recv::Closed => return Recv::Closed,
+            }
+        }
+    }
+}
+
+impl<R: Debug, F, T> Debug for FilterMapReceiver<R, F, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FilterMapReceiver")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+/// A sender which converts each message with a function before sending it.  Created by [Sender::map_send](./trait.Sender.html#method.map_send).
+///
+/// The function borrows the message, so the original message can be returned if the send fails.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use tokio::sync::mpsc;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// struct ExampleAudit {
+///     line: String,
+/// }
+///
+/// impl Message<ExampleBus> for ExampleAudit {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// #[derive(Debug)]
+/// struct ExampleLogin {
+///     user: String,
+/// }
+///
+/// // the service only depends on the message it produces
+/// async fn login(mut tx: impl lifeline::Sender<ExampleLogin>) -> anyhow::Result<()> {
+///     tx.send(ExampleLogin { user: "ferris".to_string() }).await?;
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     use lifeline::Sender;
+///
+///     let bus = ExampleBus::default();
+///     let tx = bus.tx::<ExampleAudit>()?;
+///     let mut rx = bus.rx::<ExampleAudit>()?;
+///
+///     login(tx.map_send(|login: &ExampleLogin| ExampleAudit {
+///         line: format!("login: {}", login.user),
+///     }))
+///     .await?;
+///
+///     assert_eq!("login: ferris", rx.recv().await.unwrap().line);
+///     Ok(())
+/// }
+/// ```
+pub struct WithSender<S, F, T> {
+    inner: S,
+    f: F,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<S, F, T> WithSender<S, F, T> {
+    pub(crate) fn new(inner: S, f: F) -> Self {
+        Self {
+            inner,
+            f,
+            _t: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped sender
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<S, F, T, U> Sender<U> for WithSender<S, F, T>
+where
+    S: Sender<T> + Send,
+    F: FnMut(&U) -> T + Send,
+    T: Debug + Send + 'static,
+    U: Debug + Send + 'static,
+{
+    async fn send(&mut self, value: U) -> Result<(), SendError<U>> {
+        let converted = (self.f)(&value);
+        self.inner
+            .send(converted)
+            .await
+            .map_err(|err| replace_send_error(err, value))
+    }
+
+    fn try_send(&mut self, value: U) -> Result<(), TrySendError<U>> {
+        let converted = (self.f)(&value);
+        self.inner.try_send(converted).map_err(|err| match err {
+            TrySendError::Full(_) => TrySendError::Full(value),
+            TrySendError::Closed(_) => TrySendError::Closed(value),
+        })
+    }
+
+    async fn send_timeout(&mut self, value: U, timeout: Duration) -> Result<(), SendError<U>> {
+        let converted = (self.f)(&value);
+        self.inner
+            .send_timeout(converted, timeout)
+            .await
+            .map_err(|err| replace_send_error(err, value))
+    }
+}
+
+/// Replaces the converted message in the error with the original message
+fn replace_send_error<T: Debug, U: Debug>(err: SendError<T>, value: U) -> SendError<U> {
+    match err {
+        SendError::Return(_) => SendError::Return(value),
+        SendError::Closed => SendError::Closed,
+        SendError::Timeout(_) => SendError::Timeout(value),
+        SendError::Full(_) => SendError::Full(value),
+    }
+}
+
+impl<S: Debug, F, T> Debug for WithSender<S, F, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WithSender")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
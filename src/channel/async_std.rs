@@ -1,8 +1,13 @@
 use super::Channel;
-use crate::error::SendError as LifelineSendError;
-use crate::{impl_channel_clone, impl_channel_take};
-use async_std::channel::{bounded, Receiver, Sender};
+use crate::error::{
+    SendError as LifelineSendError, TryRecvError as LifelineTryRecvError,
+    TrySendError as LifelineTrySendError,
+};
+use crate::{error::type_name, impl_channel_clone, impl_channel_take, Recv};
+use async_std::channel::{bounded, Receiver, Sender, TryRecvError, TrySendError};
 use async_trait::async_trait;
+use futures_channel::oneshot;
+use log::debug;
 use std::fmt::Debug;
 
 impl<T: Send + 'static> Channel for Sender<T> {
@@ -16,6 +21,10 @@ impl<T: Send + 'static> Channel for Sender<T> {
     fn default_capacity() -> usize {
         16
     }
+
+    fn is_closed(tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        tx.map(|tx| tx.is_closed()).unwrap_or(false)
+    }
 }
 
 impl_channel_clone!(Sender<T>);
@@ -33,6 +42,34 @@ where
 
         Ok(())
     }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        Sender::try_send(self, value).map_err(|err| match err {
+            TrySendError::Full(value) => LifelineTrySendError::Full(value),
+            TrySendError::Closed(value) => LifelineTrySendError::Closed(value),
+        })
+    }
+}
+
+#[async_trait]
+impl<T> crate::SharedSender<T> for Sender<T>
+where
+    T: Debug + Send,
+{
+    async fn send_shared(&self, value: T) -> Result<(), LifelineSendError<T>> {
+        Sender::send(self, value)
+            .await
+            .map_err(|err| LifelineSendError::Return(err.0))?;
+
+        Ok(())
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        Sender::try_send(self, value).map_err(|err| match err {
+            TrySendError::Full(value) => LifelineTrySendError::Full(value),
+            TrySendError::Closed(value) => LifelineTrySendError::Closed(value),
+        })
+    }
 }
 
 #[async_trait]
@@ -43,4 +80,503 @@ where
     async fn recv(&mut self) -> Option<T> {
         Receiver::recv(self).await.ok()
     }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        Receiver::try_recv(self).map_err(|err| match err {
+            TryRecvError::Empty => LifelineTryRecvError::Empty,
+            TryRecvError::Closed => LifelineTryRecvError::Closed,
+        })
+    }
+}
+
+/// Broadcast channels, using [async-broadcast](https://docs.rs/async-broadcast/).
+///
+/// Like tokio broadcast channels, the channel overflows rather than blocking the sender.
+/// If a receiver falls behind, the oldest messages are dropped, and the receiver skips ahead.
+impl<T: Clone + Send + 'static> Channel for async_broadcast::Sender<T> {
+    type Tx = Self;
+    type Rx = async_broadcast::Receiver<T>;
+
+    fn channel(capacity: usize) -> (Self::Tx, Self::Rx) {
+        let (mut tx, rx) = async_broadcast::broadcast(capacity);
+        tx.set_overflow(true);
+        (tx, rx)
+    }
+
+    fn default_capacity() -> usize {
+        16
+    }
+
+    fn clone_rx(rx: &mut Option<Self::Rx>, tx: Option<&Self::Tx>) -> Option<Self::Rx> {
+        // take the receiver stored on the bus first, and then subscribe using the sender
+        rx.take().or_else(|| tx.map(|tx| tx.new_receiver()))
+    }
+
+    fn rx_available(rx: Option<&Self::Rx>, tx: Option<&Self::Tx>) -> bool {
+        rx.is_some() || tx.is_some()
+    }
+
+    fn is_closed(tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        tx.map(|tx| tx.is_closed()).unwrap_or(false)
+    }
+}
+
+impl_channel_clone!(async_broadcast::Sender<T>);
+
+// this is actually overriden in clone_rx
+impl_channel_take!(async_broadcast::Receiver<T>);
+
+#[async_trait]
+impl<T> crate::Sender<T> for async_broadcast::Sender<T>
+where
+    T: Clone + Debug + Send + Sync,
+{
+    async fn send(&mut self, value: T) -> Result<(), LifelineSendError<T>> {
+        async_broadcast::Sender::broadcast(self, value)
+            .await
+            .map(|_| ())
+            .map_err(|err| LifelineSendError::Return(err.0))
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        // in overflow mode, try_broadcast returns the oldest message when the channel is full
+        match async_broadcast::Sender::try_broadcast(self, value) {
+            Ok(_) => Ok(()),
+            Err(async_broadcast::TrySendError::Closed(value)) => {
+                Err(LifelineTrySendError::Closed(value))
+            }
+            Err(async_broadcast::TrySendError::Full(value))
+            | Err(async_broadcast::TrySendError::Inactive(value)) => {
+                Err(LifelineTrySendError::Full(value))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T> crate::SharedSender<T> for async_broadcast::Sender<T>
+where
+    T: Clone + Debug + Send + Sync,
+{
+    async fn send_shared(&self, value: T) -> Result<(), LifelineSendError<T>> {
+        async_broadcast::Sender::broadcast(self, value)
+            .await
+            .map(|_| ())
+            .map_err(|err| LifelineSendError::Return(err.0))
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        match async_broadcast::Sender::try_broadcast(self, value) {
+            Ok(_) => Ok(()),
+            Err(async_broadcast::TrySendError::Closed(value)) => {
+                Err(LifelineTrySendError::Closed(value))
+            }
+            Err(async_broadcast::TrySendError::Full(value))
+            | Err(async_broadcast::TrySendError::Inactive(value)) => {
+                Err(LifelineTrySendError::Full(value))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T> crate::Receiver<T> for async_broadcast::Receiver<T>
+where
+    T: Clone + Debug + Send,
+{
+    async fn recv(&mut self) -> Option<T> {
+        loop {
+            match async_broadcast::Receiver::recv(self).await {
+                Ok(t) => return Some(t),
+                Err(async_broadcast::RecvError::Closed) => return None,
+                Err(async_broadcast::RecvError::Overflowed(n)) => {
+                    debug!("LAGGED {} {}", n, type_name::<T>());
+                    continue;
+                }
+            }
+        }
+    }
+
+    async fn recv_lossy(&mut self) -> Recv<T> {
+        match async_broadcast::Receiver::recv(self).await {
+            Ok(t) => Recv::Value(t),
+            Err(async_broadcast::RecvError::Closed) => Recv::Closed,
+            Err(async_broadcast::RecvError::Overflowed(n)) => Recv::Lagged(n),
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        loop {
+            match async_broadcast::Receiver::try_recv(self) {
+                Ok(t) => return Ok(t),
+                Err(async_broadcast::TryRecvError::Empty) => {
+                    return Err(LifelineTryRecvError::Empty)
+                }
+                Err(async_broadcast::TryRecvError::Closed) => {
+                    return Err(LifelineTryRecvError::Closed)
+                }
+                Err(async_broadcast::TryRecvError::Overflowed(n)) => {
+                    debug!("LAGGED {} {}", n, type_name::<T>());
+                    continue;
+                }
+            }
+        }
+    }
+}
+
+/// Watch channels, using [async-watch](https://docs.rs/async-watch/).  The channel is initialized with `T::default()`.
+impl<T> Channel for async_watch::Sender<T>
+where
+    T: Default + Clone + Send + Sync + 'static,
+{
+    type Tx = Self;
+    type Rx = async_watch::Receiver<T>;
+
+    fn channel(_capacity: usize) -> (Self::Tx, Self::Rx) {
+        async_watch::channel(T::default())
+    }
+
+    fn default_capacity() -> usize {
+        1
+    }
+}
+
+impl_channel_take!(async_watch::Sender<T>);
+impl_channel_clone!(async_watch::Receiver<T>);
+
+#[async_trait]
+impl<T> crate::Sender<T> for async_watch::Sender<T>
+where
+    T: Clone + Debug + Send + Sync,
+{
+    async fn send(&mut self, value: T) -> Result<(), LifelineSendError<T>> {
+        async_watch::Sender::send(self, value).map_err(|err| LifelineSendError::Return(err.value()))
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        async_watch::Sender::send(self, value)
+            .map_err(|err| LifelineTrySendError::Closed(err.value()))
+    }
+}
+
+#[async_trait]
+impl<T> crate::SharedSender<T> for async_watch::Sender<T>
+where
+    T: Clone + Debug + Send + Sync,
+{
+    async fn send_shared(&self, value: T) -> Result<(), LifelineSendError<T>> {
+        async_watch::Sender::send(self, value).map_err(|err| LifelineSendError::Return(err.value()))
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        async_watch::Sender::send(self, value)
+            .map_err(|err| LifelineTrySendError::Closed(err.value()))
+    }
+}
+
+#[async_trait]
+impl<T> crate::Receiver<T> for async_watch::Receiver<T>
+where
+    T: Clone + Debug + Send + Sync,
+{
+    async fn recv(&mut self) -> Option<T> {
+        async_watch::Receiver::recv(self).await.ok()
+    }
+}
+
+/// Oneshot channels, using [futures-channel](https://docs.rs/futures-channel/).
+impl<T: Send + 'static> Channel for oneshot::Sender<T> {
+    type Tx = Self;
+    type Rx = oneshot::Receiver<T>;
+
+    fn channel(_capacity: usize) -> (Self::Tx, Self::Rx) {
+        oneshot::channel()
+    }
+
+    fn default_capacity() -> usize {
+        1
+    }
+
+    fn is_closed(tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        tx.map(|tx| tx.is_canceled()).unwrap_or(false)
+    }
+}
+
+impl_channel_take!(oneshot::Sender<T>);
+impl_channel_take!(oneshot::Receiver<T>);
+
+#[async_trait]
+impl<T> crate::Sender<T> for oneshot::Sender<T>
+where
+    T: Debug + Send,
+{
+    async fn send(&mut self, value: T) -> Result<(), LifelineSendError<T>> {
+        // the oneshot sender is consumed by send.  it's replaced with a sender which is already closed,
+        // so a second send returns the value.
+        let (closed, _) = oneshot::channel();
+        let tx = std::mem::replace(self, closed);
+
+        tx.send(value).map_err(LifelineSendError::Return)
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        let (closed, _) = oneshot::channel();
+        let tx = std::mem::replace(self, closed);
+
+        tx.send(value).map_err(LifelineTrySendError::Closed)
+    }
+}
+
+#[async_trait]
+impl<T> crate::Receiver<T> for oneshot::Receiver<T>
+where
+    T: Debug + Send,
+{
+    async fn recv(&mut self) -> Option<T> {
+        // the receiver returns Canceled once the value has been received
+        self.await.ok()
+    }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        match oneshot::Receiver::try_recv(self) {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => Err(LifelineTryRecvError::Empty),
+            Err(_) => Err(LifelineTryRecvError::Closed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::{SendError, TakeChannelError, TryRecvError, TrySendError},
+        lifeline_bus, Bus, Message, Receiver, Recv, Sender, SharedSender,
+    };
+    use futures_channel::oneshot;
+
+    lifeline_bus!(struct AsyncStdBus);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Broadcast(usize);
+
+    impl Message<AsyncStdBus> for Broadcast {
+        type Channel = async_broadcast::Sender<Self>;
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct State(usize);
+
+    impl Message<AsyncStdBus> for State {
+        type Channel = async_watch::Sender<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Shutdown;
+
+    impl Message<AsyncStdBus> for Shutdown {
+        type Channel = oneshot::Sender<Self>;
+    }
+
+    #[tokio::test]
+    async fn broadcast_subscribers_receive_all() {
+        let bus = AsyncStdBus::default();
+        let mut tx = bus.tx::<Broadcast>().unwrap();
+        let mut rx_1 = bus.rx::<Broadcast>().unwrap();
+        let mut rx_2 = bus.rx::<Broadcast>().unwrap();
+
+        Sender::send(&mut tx, Broadcast(1)).await.unwrap();
+
+        assert_eq!(Some(Broadcast(1)), Receiver::recv(&mut rx_1).await);
+        assert_eq!(Some(Broadcast(1)), Receiver::recv(&mut rx_2).await);
+    }
+
+    #[tokio::test]
+    async fn broadcast_overflows() {
+        let bus = AsyncStdBus::default();
+        bus.capacity::<Broadcast>(2).unwrap();
+
+        let mut tx = bus.tx::<Broadcast>().unwrap();
+        let mut rx = bus.rx::<Broadcast>().unwrap();
+        let mut lossy = bus.rx::<Broadcast>().unwrap();
+
+        for n in 0..3 {
+            Sender::try_send(&mut tx, Broadcast(n)).unwrap();
+        }
+
+        assert_eq!(Some(Broadcast(1)), Receiver::recv(&mut rx).await);
+        assert_eq!(Recv::Lagged(1), Receiver::recv_lossy(&mut lossy).await);
+        assert_eq!(
+            Recv::Value(Broadcast(1)),
+            Receiver::recv_lossy(&mut lossy).await
+        );
+    }
+
+    #[tokio::test]
+    async fn broadcast_closed() {
+        let bus = AsyncStdBus::default();
+        let mut tx = bus.tx::<Broadcast>().unwrap();
+        let mut rx = bus.rx::<Broadcast>().unwrap();
+
+        Sender::send(&mut tx, Broadcast(1)).await.unwrap();
+        drop(tx);
+        drop(bus);
+
+        assert_eq!(Ok(Broadcast(1)), Receiver::try_recv(&mut rx));
+        assert_eq!(Err(TryRecvError::Closed), Receiver::try_recv(&mut rx));
+        assert_eq!(None, Receiver::recv(&mut rx).await);
+    }
+
+    #[tokio::test]
+    async fn broadcast_closed_when_receivers_dropped() {
+        let bus = AsyncStdBus::default();
+        let mut tx = bus.tx::<Broadcast>().unwrap();
+        drop(bus.rx::<Broadcast>().unwrap());
+        drop(bus);
+
+        assert_eq!(
+            Err(SendError::Return(Broadcast(1))),
+            Sender::send(&mut tx, Broadcast(1)).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(Broadcast(2))),
+            Sender::try_send(&mut tx, Broadcast(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn watch_receives_latest() {
+        let bus = AsyncStdBus::default();
+        let mut tx = bus.tx::<State>().unwrap();
+        let mut rx = bus.rx::<State>().unwrap();
+
+        Sender::send(&mut tx, State(1)).await.unwrap();
+        Sender::send(&mut tx, State(2)).await.unwrap();
+        assert_eq!(Some(State(2)), Receiver::recv(&mut rx).await);
+
+        drop(tx);
+        drop(bus);
+        assert_eq!(None, Receiver::recv(&mut rx).await);
+    }
+
+    #[test]
+    fn watch_tx_taken_twice() {
+        let bus = AsyncStdBus::default();
+        let _tx = bus.tx::<State>().unwrap();
+
+        assert!(matches!(
+            bus.tx::<State>(),
+            Err(TakeChannelError::AlreadyTaken(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn oneshot_sends_once() {
+        let bus = AsyncStdBus::default();
+        let mut tx = bus.tx::<Shutdown>().unwrap();
+        let mut rx = bus.rx::<Shutdown>().unwrap();
+
+        assert_eq!(Err(TryRecvError::Empty), Receiver::try_recv(&mut rx));
+        Sender::send(&mut tx, Shutdown).await.unwrap();
+        assert_eq!(
+            Err(SendError::Return(Shutdown)),
+            Sender::send(&mut tx, Shutdown).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(Shutdown)),
+            Sender::try_send(&mut tx, Shutdown)
+        );
+
+        assert_eq!(Some(Shutdown), Receiver::recv(&mut rx).await);
+        assert_eq!(None, Receiver::recv(&mut rx).await);
+    }
+
+    #[tokio::test]
+    async fn oneshot_closed_when_rx_dropped() {
+        let bus = AsyncStdBus::default();
+        let mut tx = bus.tx::<Shutdown>().unwrap();
+        drop(bus.rx::<Shutdown>().unwrap());
+
+        assert!(matches!(
+            bus.rx::<Shutdown>(),
+            Err(TakeChannelError::AlreadyTaken(_))
+        ));
+        assert_eq!(
+            Err(TrySendError::Closed(Shutdown)),
+            Sender::try_send(&mut tx, Shutdown)
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Queued(usize);
+
+    impl Message<AsyncStdBus> for Queued {
+        type Channel = async_std::channel::Sender<Self>;
+    }
+
+    #[tokio::test]
+    async fn mpsc_send_shared() {
+        let bus = AsyncStdBus::default();
+        bus.capacity::<Queued>(2).unwrap();
+
+        let tx = bus.tx::<Queued>().unwrap();
+        let mut rx = bus.rx::<Queued>().unwrap();
+
+        let (first, second) = tokio::join!(tx.send_shared(Queued(1)), tx.send_shared(Queued(2)));
+        assert_eq!(Ok(()), first);
+        assert_eq!(Ok(()), second);
+        assert_eq!(
+            Err(TrySendError::Full(Queued(3))),
+            tx.try_send_shared(Queued(3))
+        );
+
+        assert_eq!(Some(Queued(1)), Receiver::recv(&mut rx).await);
+        assert_eq!(Ok(()), tx.try_send_shared(Queued(3)));
+    }
+
+    #[tokio::test]
+    async fn mpsc_send_shared_with_dropped_receiver() {
+        let bus = AsyncStdBus::default();
+        let tx = bus.tx::<Queued>().unwrap();
+        drop(bus.rx::<Queued>().unwrap());
+        drop(bus);
+
+        assert_eq!(
+            Err(SendError::Return(Queued(1))),
+            tx.send_shared(Queued(1)).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(Queued(2))),
+            tx.try_send_shared(Queued(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn broadcast_send_shared() {
+        let bus = AsyncStdBus::default();
+        let tx = bus.tx::<Broadcast>().unwrap();
+        let mut rx = bus.rx::<Broadcast>().unwrap();
+
+        tx.send_shared(Broadcast(1)).await.unwrap();
+        tx.try_send_shared(Broadcast(2)).unwrap();
+
+        assert_eq!(Some(Broadcast(1)), Receiver::recv(&mut rx).await);
+        assert_eq!(Some(Broadcast(2)), Receiver::recv(&mut rx).await);
+    }
+
+    #[tokio::test]
+    async fn watch_send_shared() {
+        let bus = AsyncStdBus::default();
+        let tx = bus.tx::<State>().unwrap();
+        let mut rx = bus.rx::<State>().unwrap();
+
+        tx.send_shared(State(1)).await.unwrap();
+        assert_eq!(Some(State(1)), Receiver::recv(&mut rx).await);
+
+        drop(rx);
+        drop(bus);
+        assert_eq!(
+            Err(SendError::Return(State(2))),
+            tx.send_shared(State(2)).await
+        );
+    }
 }
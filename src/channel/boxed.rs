@@ -0,0 +1,249 @@
+use crate::{
+    error::{SendError, TryRecvError, TrySendError},
+    Receiver, Recv, Sender,
+};
+use async_trait::async_trait;
+use std::{fmt::Debug, time::Duration};
+
+/// A type-erased [Sender](./trait.Sender.html), which can wrap the sender of any channel backend.
+///
+/// Library services can take a `BoxSender<T>`, and be used with buses which carry the message on tokio, postage, async-std, or flume channels.
+/// Boxed senders are usually taken with [Bus::tx_boxed](./trait.Bus.html#method.tx_boxed).
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::{BoxReceiver, BoxSender};
+///
+/// lifeline_bus!(pub struct TokioBus);
+/// lifeline_bus!(pub struct PostageBus);
+///
+/// #[derive(Debug, Clone)]
+/// struct ExampleMessage(usize);
+///
+/// impl Message<TokioBus> for ExampleMessage {
+///     type Channel = tokio::sync::mpsc::Sender<Self>;
+/// }
+///
+/// impl Message<PostageBus> for ExampleMessage {
+///     type Channel = postage::mpsc::Sender<Self>;
+/// }
+///
+/// // the library function is written once, and works with either bus
+/// async fn echo(
+///     mut tx: BoxSender<ExampleMessage>,
+///     mut rx: BoxReceiver<ExampleMessage>,
+/// ) -> Option<ExampleMessage> {
+///     use lifeline::{Receiver, Sender};
+///
+///     tx.send(ExampleMessage(1)).await.ok()?;
+///     rx.recv().await
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let bus = TokioBus::default();
+///     let received = echo(bus.tx_boxed()?, bus.rx_boxed()?).await;
+///     assert!(received.is_some());
+///
+///     let bus = PostageBus::default();
+///     let received = echo(bus.tx_boxed()?, bus.rx_boxed()?).await;
+///     assert!(received.is_some());
+///     Ok(())
+/// }
+/// ```
+pub struct BoxSender<T> {
+    inner: Box<dyn Sender<T> + Send>,
+}
+
+impl<T: Debug> BoxSender<T> {
+    /// Boxes the sender
+    pub fn new(tx: impl Sender<T> + Send + 'static) -> Self {
+        Self {
+            inner: Box::new(tx),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> Sender<T> for BoxSender<T>
+where
+    T: Debug + Send + 'static,
+{
+    async fn send(&mut self, value: T) -> Result<(), SendError<T>> {
+        self.inner.send(value).await
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        self.inner.try_send(value)
+    }
+
+    async fn send_timeout(&mut self, value: T, timeout: Duration) -> Result<(), SendError<T>> {
+        self.inner.send_timeout(value, timeout).await
+    }
+}
+
+impl<T> Debug for BoxSender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoxSender").finish()
+    }
+}
+
+/// A type-erased [Receiver](./trait.Receiver.html), which can wrap the receiver of any channel backend.
+///
+/// Boxed receivers are usually taken with [Bus::rx_boxed](./trait.Bus.html#method.rx_boxed).  See [BoxSender](./struct.BoxSender.html) for an example.
+pub struct BoxReceiver<T> {
+    inner: Box<dyn Receiver<T> + Send>,
+}
+
+impl<T> BoxReceiver<T> {
+    /// Boxes the receiver
+    pub fn new(rx: impl Receiver<T> + Send + 'static) -> Self {
+        Self {
+            inner: Box::new(rx),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> Receiver<T> for BoxReceiver<T>
+where
+    T: Send + 'static,
+{
+    async fn recv(&mut self) -> Option<T> {
+        self.inner.recv().await
+    }
+
+    fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.inner.try_recv()
+    }
+
+    async fn recv_lossy(&mut self) -> Recv<T> {
+        self.inner.recv_lossy().await
+    }
+
+    async fn recv_many(&mut self, buffer: &mut Vec<T>, limit: usize) -> usize {
+        self.inner.recv_many(buffer, limit).await
+    }
+}
+
+impl<T> Debug for BoxReceiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoxReceiver").finish()
+    }
+}
+
+#[cfg(all(test, feature = "tokio-channels"))]
+mod tests {
+    use super::{BoxReceiver, BoxSender};
+    use crate::{
+        error::{SendError, TakeChannelError, TryRecvError, TrySendError},
+        lifeline_bus, Bus, Message, Receiver, Recv, Sender,
+    };
+    use tokio::sync::{broadcast, mpsc};
+
+    lifeline_bus!(struct BoxedBus);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Mpsc(usize);
+
+    impl Message<BoxedBus> for Mpsc {
+        type Channel = mpsc::Sender<Self>;
+    }
+
+    #[cfg(feature = "postage-channels")]
+    #[derive(Debug, Clone, PartialEq)]
+    struct Postage(usize);
+
+    #[cfg(feature = "postage-channels")]
+    impl Message<BoxedBus> for Postage {
+        type Channel = postage::mpsc::Sender<Self>;
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Broadcast(usize);
+
+    impl Message<BoxedBus> for Broadcast {
+        type Channel = broadcast::Sender<Self>;
+    }
+
+    #[tokio::test]
+    async fn boxed_channel_sends_and_receives() {
+        let (tx, rx) = mpsc::channel(1);
+        let mut tx = BoxSender::new(tx);
+        let mut rx = BoxReceiver::new(rx);
+
+        assert_eq!(Ok(()), tx.send(Mpsc(1)).await);
+        assert_eq!(Err(TrySendError::Full(Mpsc(2))), tx.try_send(Mpsc(2)));
+        assert_eq!(Some(Mpsc(1)), rx.recv().await);
+        assert_eq!(Err(TryRecvError::Empty), rx.try_recv());
+    }
+
+    #[cfg(feature = "postage-channels")]
+    #[tokio::test]
+    async fn tx_boxed_and_rx_boxed() {
+        let bus = BoxedBus::default();
+
+        let mut tx = bus.tx_boxed::<Mpsc>().unwrap();
+        let mut rx = bus.rx_boxed::<Mpsc>().unwrap();
+        tx.send(Mpsc(1)).await.unwrap();
+        assert_eq!(Some(Mpsc(1)), rx.recv().await);
+
+        let mut tx = bus.tx_boxed::<Postage>().unwrap();
+        let mut rx = bus.rx_boxed::<Postage>().unwrap();
+        tx.send(Postage(2)).await.unwrap();
+        assert_eq!(Some(Postage(2)), rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn boxed_sender_with_dropped_receiver() {
+        let bus = BoxedBus::default();
+
+        let mut tx = bus.tx_boxed::<Mpsc>().unwrap();
+        drop(bus.rx_boxed::<Mpsc>().unwrap());
+        drop(bus);
+
+        assert_eq!(Err(SendError::Return(Mpsc(1))), tx.send(Mpsc(1)).await);
+        assert_eq!(Err(TrySendError::Closed(Mpsc(2))), tx.try_send(Mpsc(2)));
+    }
+
+    #[tokio::test]
+    async fn boxed_receiver_ends_when_senders_dropped() {
+        let bus = BoxedBus::default();
+
+        let mut rx = bus.rx_boxed::<Mpsc>().unwrap();
+        drop(bus.tx_boxed::<Mpsc>().unwrap());
+        drop(bus);
+
+        assert_eq!(None, rx.recv().await);
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[test]
+    fn receiver_taken_twice() {
+        let bus = BoxedBus::default();
+
+        let _rx = bus.rx_boxed::<Mpsc>().unwrap();
+        assert!(matches!(
+            bus.rx_boxed::<Mpsc>(),
+            Err(TakeChannelError::AlreadyTaken(_))
+        ));
+
+        // senders are cloned, so they can be taken more than once
+        assert!(bus.tx_boxed::<Mpsc>().is_ok());
+        assert!(bus.tx_boxed::<Mpsc>().is_ok());
+    }
+
+    #[tokio::test]
+    async fn broadcast_receivers_taken_twice() {
+        let bus = BoxedBus::default();
+
+        let mut rx1 = bus.rx_boxed::<Broadcast>().unwrap();
+        let mut rx2 = bus.rx_boxed::<Broadcast>().unwrap();
+        let mut tx = bus.tx_boxed::<Broadcast>().unwrap();
+
+        tx.send(Broadcast(1)).await.unwrap();
+        assert_eq!(Some(Broadcast(1)), rx1.recv().await);
+        assert_eq!(Recv::Value(Broadcast(1)), rx2.recv_lossy().await);
+    }
+}
@@ -0,0 +1,370 @@
+use super::Channel;
+use crate::error::{SendError, TryRecvError, TrySendError};
+use crate::{channel::shared::Shared, Storage};
+use async_trait::async_trait;
+use futures_util::future::poll_fn;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+};
+
+/// Extracts the key of a message carried by a [CoalesceSender](./struct.CoalesceSender.html).
+///
+/// Messages with the same key replace each other, and only the latest message for the key is delivered.
+pub trait Keyed {
+    /// The key type, such as a tab or session id
+    type Key: Eq + Hash + Clone + Send;
+
+    /// Returns the key of the message
+    fn key(&self) -> Self::Key;
+}
+
+/// A lifeline-native coalescing channel, which keeps only the latest message for each key.
+///
+/// It behaves like a `watch` channel for each key, without one channel per key.
+/// Each changed key is delivered once, in the order the keys first changed.
+/// If a key changes again before it's received, the pending message is replaced and the key keeps its place.
+///
+/// The message type provides the key with the [Keyed](./trait.Keyed.html) trait.
+/// Senders never wait, so the channel capacity is ignored.  The sender is clonable, and the receiver is taken.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::{CoalesceSender, Keyed, Receiver, Sender};
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug, PartialEq)]
+/// struct ExampleTabState {
+///     tab: usize,
+///     title: String,
+/// }
+///
+/// impl Keyed for ExampleTabState {
+///     type Key = usize;
+///
+///     fn key(&self) -> usize {
+///         self.tab
+///     }
+/// }
+///
+/// impl Message<ExampleBus> for ExampleTabState {
+///     type Channel = CoalesceSender<Self>;
+/// }
+///
+/// fn state(tab: usize, title: &str) -> ExampleTabState {
+///     ExampleTabState {
+///         tab,
+///         title: title.to_string(),
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     let mut tx = bus.tx::<ExampleTabState>()?;
+///     let mut rx = bus.rx::<ExampleTabState>()?;
+///
+///     tx.send(state(1, "loading")).await?;
+///     tx.send(state(2, "docs")).await?;
+///     tx.send(state(1, "lifeline")).await?;
+///
+///     assert_eq!(Some(state(1, "lifeline")), rx.recv().await);
+///     assert_eq!(Some(state(2, "docs")), rx.recv().await);
+///     Ok(())
+/// }
+/// ```
+pub struct CoalesceSender<T: Keyed> {
+    shared: Shared<Queue<T>>,
+}
+
+/// The receiver of a [CoalesceSender](./struct.CoalesceSender.html).  Receives the latest message for each changed key.
+pub struct CoalesceReceiver<T: Keyed> {
+    shared: Shared<Queue<T>>,
+}
+
+struct Queue<T: Keyed> {
+    latest: HashMap<T::Key, T>,
+    changed: VecDeque<T::Key>,
+}
+
+impl<T: Keyed> Queue<T> {
+    fn push(&mut self, value: T) {
+        let key = value.key();
+
+        if self.latest.insert(key.clone(), value).is_none() {
+            self.changed.push_back(key);
+        }
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let key = self.changed.pop_front()?;
+        self.latest.remove(&key)
+    }
+}
+
+impl<T: Keyed> CoalesceSender<T> {
+    /// Returns the number of keys which have changed, and have not been received.
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.changed.len()
+    }
+
+    /// Returns true if no keys are waiting to be received.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the receiver has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+
+    /// Stores the value as the latest message for its key.  Returns the value if the receiver has been dropped.
+    fn store(&self, value: T) -> Result<(), T> {
+        let mut state = self.shared.lock();
+
+        if !state.receiver {
+            return Err(value);
+        }
+
+        state.queue.push(value);
+        state.wake_receiver();
+        Ok(())
+    }
+}
+
+impl<T: Keyed> Clone for CoalesceSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone_sender(),
+        }
+    }
+}
+
+impl<T: Keyed> Drop for CoalesceSender<T> {
+    fn drop(&mut self) {
+        self.shared.drop_sender();
+    }
+}
+
+impl<T: Keyed> Debug for CoalesceSender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CoalesceSender")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T: Keyed> CoalesceReceiver<T> {
+    /// Returns the number of keys which have changed, and have not been received.
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.changed.len()
+    }
+
+    /// Returns true if no keys are waiting to be received.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Keyed> Drop for CoalesceReceiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.drop_receiver();
+        state.queue.latest.clear();
+        state.queue.changed.clear();
+    }
+}
+
+impl<T: Keyed> Debug for CoalesceReceiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CoalesceReceiver")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T> Channel for CoalesceSender<T>
+where
+    T: Keyed + Send + 'static,
+{
+    type Tx = Self;
+    type Rx = CoalesceReceiver<T>;
+
+    fn channel(_capacity: usize) -> (Self::Tx, Self::Rx) {
+        let (tx, rx) = Shared::channel(Queue {
+            latest: HashMap::new(),
+            changed: VecDeque::new(),
+        });
+
+        (
+            CoalesceSender { shared: tx },
+            CoalesceReceiver { shared: rx },
+        )
+    }
+
+    fn default_capacity() -> usize {
+        1
+    }
+
+    fn is_closed(tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        tx.map(CoalesceSender::is_closed).unwrap_or(false)
+    }
+}
+
+impl<T: Keyed + Send + 'static> Storage for CoalesceSender<T> {
+    fn take_or_clone(res: &mut Option<Self>) -> Option<Self> {
+        Self::clone_slot(res)
+    }
+}
+
+impl<T: Keyed + Send + 'static> Storage for CoalesceReceiver<T> {
+    fn take_or_clone(res: &mut Option<Self>) -> Option<Self> {
+        Self::take_slot(res)
+    }
+}
+
+#[async_trait]
+impl<T> crate::Sender<T> for CoalesceSender<T>
+where
+    T: Keyed + Debug + Send,
+{
+    async fn send(&mut self, value: T) -> Result<(), SendError<T>> {
+        self.store(value).map_err(SendError::Return)
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        self.store(value).map_err(TrySendError::Closed)
+    }
+}
+
+#[async_trait]
+impl<T> crate::SharedSender<T> for CoalesceSender<T>
+where
+    T: Keyed + Debug + Send,
+{
+    async fn send_shared(&self, value: T) -> Result<(), SendError<T>> {
+        self.store(value).map_err(SendError::Return)
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), TrySendError<T>> {
+        self.store(value).map_err(TrySendError::Closed)
+    }
+}
+
+#[async_trait]
+impl<T> crate::Receiver<T> for CoalesceReceiver<T>
+where
+    T: Keyed + Send,
+{
+    async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.shared.poll_recv(cx, Queue::pop)).await
+    }
+
+    fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.shared.try_recv(Queue::pop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoalesceSender, Keyed};
+    use crate::{
+        error::{SendError, TryRecvError, TrySendError},
+        Channel, Receiver, Sender,
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct Update {
+        session: &'static str,
+        value: usize,
+    }
+
+    impl Keyed for Update {
+        type Key = &'static str;
+
+        fn key(&self) -> &'static str {
+            self.session
+        }
+    }
+
+    fn update(session: &'static str, value: usize) -> Update {
+        Update { session, value }
+    }
+
+    #[tokio::test]
+    async fn changed_keys_are_delivered_once() {
+        let (mut tx, mut rx) = CoalesceSender::channel(1);
+
+        tx.send(update("a", 1)).await.unwrap();
+        tx.send(update("b", 1)).await.unwrap();
+        tx.send(update("a", 2)).await.unwrap();
+        tx.send(update("a", 3)).await.unwrap();
+        assert_eq!(2, tx.len());
+
+        assert_eq!(Some(update("a", 3)), rx.recv().await);
+
+        // a key which changes after it's received is delivered again
+        tx.send(update("a", 4)).await.unwrap();
+        drop(tx);
+
+        assert_eq!(Some(update("b", 1)), rx.recv().await);
+        assert_eq!(Some(update("a", 4)), rx.recv().await);
+        assert_eq!(None, rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn dropped_receiver_returns_message() {
+        let (mut tx, rx) = CoalesceSender::channel(1);
+        drop(rx);
+
+        assert!(tx.is_closed());
+        assert_eq!(
+            Err(SendError::Return(update("a", 1))),
+            tx.send(update("a", 1)).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(update("a", 2))),
+            tx.try_send(update("a", 2))
+        );
+    }
+
+    #[test]
+    fn try_recv_empty_and_closed() {
+        let (mut tx, mut rx) = CoalesceSender::channel(1);
+        assert_eq!(Err(TryRecvError::Empty), rx.try_recv());
+
+        tx.try_send(update("a", 1)).unwrap();
+        drop(tx);
+
+        // buffered keys are received after the senders are dropped
+        assert_eq!(Ok(update("a", 1)), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[tokio::test]
+    async fn cloned_senders_keep_channel_open() {
+        let (tx, mut rx) = CoalesceSender::channel(1);
+        let mut cloned = tx.clone();
+        drop(tx);
+
+        cloned.send(update("a", 1)).await.unwrap();
+        assert_eq!(Some(update("a", 1)), rx.recv().await);
+        assert_eq!(Err(TryRecvError::Empty), rx.try_recv());
+
+        drop(cloned);
+        assert_eq!(None, rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn waiting_receiver_is_woken() {
+        let (mut tx, mut rx) = CoalesceSender::channel(1);
+
+        let receiver = tokio::spawn(async move { rx.recv().await });
+        tokio::task::yield_now().await;
+
+        tx.send(update("a", 1)).await.unwrap();
+        assert_eq!(Some(update("a", 1)), receiver.await.unwrap());
+    }
+}
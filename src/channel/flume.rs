@@ -0,0 +1,290 @@
+use super::Channel;
+use crate::error::{
+    SendError as LifelineSendError, TryRecvError as LifelineTryRecvError,
+    TrySendError as LifelineTrySendError,
+};
+use crate::impl_channel_clone;
+use async_trait::async_trait;
+use flume::{bounded, unbounded, Receiver, Sender, TryRecvError, TrySendError};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// Bounded flume channels.  Receivers are clonable, so one message can be consumed by a pool of workers.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// struct ExampleJob {}
+/// impl Message<ExampleBus> for ExampleJob {
+///     type Channel = flume::Sender<Self>;
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     let tx = bus.tx::<ExampleJob>()?;
+///     let worker_1 = bus.rx::<ExampleJob>()?;
+///     let worker_2 = bus.rx::<ExampleJob>()?;
+///
+///     // flume senders can be used from blocking threads
+///     std::thread::spawn(move || tx.send(ExampleJob {})).join().unwrap()?;
+///
+///     assert!(worker_1.try_recv().is_ok() || worker_2.try_recv().is_ok());
+///     Ok(())
+/// }
+/// ```
+impl<T: Send + 'static> Channel for Sender<T> {
+    type Tx = Self;
+    type Rx = Receiver<T>;
+
+    fn channel(capacity: usize) -> (Self::Tx, Self::Rx) {
+        bounded(capacity)
+    }
+
+    fn default_capacity() -> usize {
+        16
+    }
+
+    fn is_closed(tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        tx.map(|tx| tx.is_disconnected()).unwrap_or(false)
+    }
+}
+
+/// An unbounded flume channel, which never applies backpressure to the sender.  The capacity configured with `bus.capacity` is ignored.
+///
+/// Flume uses the same Sender and Receiver types for bounded and unbounded channels.
+/// This type selects the unbounded constructor, and is used as the `Message` channel type:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::FlumeUnbounded;
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// struct ExampleEvent {}
+/// impl Message<ExampleBus> for ExampleEvent {
+///     type Channel = FlumeUnbounded<Self>;
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     let tx: flume::Sender<ExampleEvent> = bus.tx::<ExampleEvent>()?;
+///     let rx: flume::Receiver<ExampleEvent> = bus.rx::<ExampleEvent>()?;
+///
+///     for _ in 0..1024 {
+///         tx.send(ExampleEvent {})?;
+///     }
+///
+///     assert_eq!(1024, rx.len());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Unbounded<T> {
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T: Send + 'static> Channel for Unbounded<T> {
+    type Tx = Sender<T>;
+    type Rx = Receiver<T>;
+
+    fn channel(_capacity: usize) -> (Self::Tx, Self::Rx) {
+        unbounded()
+    }
+
+    fn default_capacity() -> usize {
+        0
+    }
+
+    fn is_closed(tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        tx.map(|tx| tx.is_disconnected()).unwrap_or(false)
+    }
+}
+
+impl_channel_clone!(Sender<T>);
+impl_channel_clone!(Receiver<T>);
+
+#[async_trait]
+impl<T> crate::Sender<T> for Sender<T>
+where
+    T: Debug + Send,
+{
+    async fn send(&mut self, value: T) -> Result<(), LifelineSendError<T>> {
+        Sender::send_async(self, value)
+            .await
+            .map_err(|err| LifelineSendError::Return(err.0))
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        Sender::try_send(self, value).map_err(|err| match err {
+            TrySendError::Full(value) => LifelineTrySendError::Full(value),
+            TrySendError::Disconnected(value) => LifelineTrySendError::Closed(value),
+        })
+    }
+}
+
+#[async_trait]
+impl<T> crate::SharedSender<T> for Sender<T>
+where
+    T: Debug + Send,
+{
+    async fn send_shared(&self, value: T) -> Result<(), LifelineSendError<T>> {
+        Sender::send_async(self, value)
+            .await
+            .map_err(|err| LifelineSendError::Return(err.0))
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        Sender::try_send(self, value).map_err(|err| match err {
+            TrySendError::Full(value) => LifelineTrySendError::Full(value),
+            TrySendError::Disconnected(value) => LifelineTrySendError::Closed(value),
+        })
+    }
+}
+
+#[async_trait]
+impl<T> crate::Receiver<T> for Receiver<T>
+where
+    T: Debug + Send,
+{
+    async fn recv(&mut self) -> Option<T> {
+        Receiver::recv_async(self).await.ok()
+    }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        Receiver::try_recv(self).map_err(|err| match err {
+            TryRecvError::Empty => LifelineTryRecvError::Empty,
+            TryRecvError::Disconnected => LifelineTryRecvError::Closed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Unbounded;
+    use crate::{
+        error::{SendError, TryRecvError, TrySendError},
+        lifeline_bus, Bus, Message, Receiver, Sender, SharedSender,
+    };
+
+    lifeline_bus!(struct FlumeBus);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Job(usize);
+
+    impl Message<FlumeBus> for Job {
+        type Channel = flume::Sender<Self>;
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Event(usize);
+
+    impl Message<FlumeBus> for Event {
+        type Channel = Unbounded<Self>;
+    }
+
+    #[tokio::test]
+    async fn bounded_uses_capacity() {
+        let bus = FlumeBus::default();
+        bus.capacity::<Job>(1).unwrap();
+
+        let mut tx = bus.tx::<Job>().unwrap();
+        let mut rx = bus.rx::<Job>().unwrap();
+
+        Sender::try_send(&mut tx, Job(1)).unwrap();
+        assert_eq!(
+            Err(TrySendError::Full(Job(2))),
+            Sender::try_send(&mut tx, Job(2))
+        );
+
+        assert_eq!(Some(Job(1)), Receiver::recv(&mut rx).await);
+    }
+
+    #[tokio::test]
+    async fn workers_share_messages() {
+        let bus = FlumeBus::default();
+        let mut tx = bus.tx::<Job>().unwrap();
+        let mut worker_1 = bus.rx::<Job>().unwrap();
+        let mut worker_2 = bus.rx::<Job>().unwrap();
+
+        Sender::send(&mut tx, Job(1)).await.unwrap();
+        Sender::send(&mut tx, Job(2)).await.unwrap();
+
+        assert_eq!(Ok(Job(1)), Receiver::try_recv(&mut worker_1));
+        assert_eq!(Ok(Job(2)), Receiver::try_recv(&mut worker_2));
+        assert_eq!(Err(TryRecvError::Empty), Receiver::try_recv(&mut worker_1));
+    }
+
+    #[tokio::test]
+    async fn closed_once_bus_and_receivers_dropped() {
+        let bus = FlumeBus::default();
+        let mut tx = bus.tx::<Job>().unwrap();
+        let rx = bus.rx::<Job>().unwrap();
+
+        // the bus holds a receiver, so the channel stays open
+        drop(rx);
+        Sender::try_send(&mut tx, Job(1)).unwrap();
+
+        drop(bus);
+        assert_eq!(
+            Err(SendError::Return(Job(2))),
+            Sender::send(&mut tx, Job(2)).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(Job(3))),
+            Sender::try_send(&mut tx, Job(3))
+        );
+    }
+
+    #[tokio::test]
+    async fn unbounded_ignores_capacity() {
+        let bus = FlumeBus::default();
+        bus.capacity::<Event>(1).unwrap();
+
+        let mut tx = bus.tx::<Event>().unwrap();
+        let mut rx = bus.rx::<Event>().unwrap();
+        drop(bus);
+
+        for n in 0..16 {
+            Sender::try_send(&mut tx, Event(n)).unwrap();
+        }
+        drop(tx);
+
+        for n in 0..16 {
+            assert_eq!(Some(Event(n)), Receiver::recv(&mut rx).await);
+        }
+        assert_eq!(None, Receiver::recv(&mut rx).await);
+        assert_eq!(Err(TryRecvError::Closed), Receiver::try_recv(&mut rx));
+    }
+
+    #[tokio::test]
+    async fn bounded_send_shared() {
+        let bus = FlumeBus::default();
+        bus.capacity::<Job>(2).unwrap();
+
+        let tx = bus.tx::<Job>().unwrap();
+        let mut rx = bus.rx::<Job>().unwrap();
+
+        let (first, second) = tokio::join!(tx.send_shared(Job(1)), tx.send_shared(Job(2)));
+        assert_eq!(Ok(()), first);
+        assert_eq!(Ok(()), second);
+        assert_eq!(Err(TrySendError::Full(Job(3))), tx.try_send_shared(Job(3)));
+
+        assert_eq!(Some(Job(1)), Receiver::recv(&mut rx).await);
+        assert_eq!(Ok(()), tx.try_send_shared(Job(3)));
+    }
+
+    #[tokio::test]
+    async fn send_shared_with_dropped_receiver() {
+        let bus = FlumeBus::default();
+        let tx = bus.tx::<Job>().unwrap();
+        drop(bus.rx::<Job>().unwrap());
+        drop(bus);
+
+        assert_eq!(Err(SendError::Return(Job(1))), tx.send_shared(Job(1)).await);
+        assert_eq!(
+            Err(TrySendError::Closed(Job(2))),
+            tx.try_send_shared(Job(2))
+        );
+    }
+}
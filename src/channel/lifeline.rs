@@ -1,23 +1,251 @@
-use crate::error::SendError;
+use super::adapter::{FilterMapReceiver, FilterReceiver, MapReceiver, WithSender};
+use super::stream::{ReceiverStream, SenderSink};
+use crate::error::{SendError, TryRecvError, TrySendError};
+use crate::spawn::sleep;
 use async_trait::async_trait;
-use std::fmt::Debug;
+use futures_util::{
+    future::{poll_fn, select, Either},
+    pin_mut, FutureExt,
+};
+use std::{
+    fmt::Debug,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// The maximum delay between retries in the default `Sender::send_timeout` implementation
+const SEND_TIMEOUT_MAX_BACKOFF: Duration = Duration::from_millis(50);
 
 impl<T: Debug> SendError<T> {
     pub fn take_message(self) -> Option<T> {
         match self {
             SendError::Return(value) => Some(value),
             SendError::Closed => None,
+            SendError::Timeout(value) => Some(value),
+            SendError::Full(value) => Some(value),
         }
     }
 }
 
+impl<T: Debug> TrySendError<T> {
+    /// Returns the message which could not be sent
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(value) => value,
+            TrySendError::Closed(value) => value,
+        }
+    }
+}
+
+/// Polls the send until it completes, or the timeout elapses.  Used to implement `send_timeout` for channels which can poll for capacity.
+///
+/// The poll function takes the value from the slot when the send completes, and leaves it in the slot while the send is pending.
+/// If the timeout elapses, the value is returned in `SendError::Timeout`.
+pub(crate) async fn send_until<T, F>(
+    value: T,
+    timeout: Option<Duration>,
+    mut poll_send: F,
+) -> Result<(), SendError<T>>
+where
+    T: Debug,
+    F: FnMut(&mut Context<'_>, &mut Option<T>) -> Poll<Result<(), SendError<T>>>,
+{
+    let mut slot = Some(value);
+
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return poll_fn(|cx| poll_send(cx, &mut slot)).await,
+    };
+
+    let sent = {
+        let send = poll_fn(|cx| poll_send(cx, &mut slot));
+        let timer = sleep(timeout);
+        pin_mut!(send, timer);
+
+        match select(send, timer).await {
+            Either::Left((result, _)) => Some(result),
+            Either::Right(_) => None,
+        }
+    };
+
+    sent.unwrap_or_else(|| {
+        let value = slot
+            .take()
+            .expect("the value is held until the send completes");
+        Err(SendError::Timeout(value))
+    })
+}
+
+/// The outcome of [Receiver::recv_lossy](./trait.Receiver.html#method.recv_lossy).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recv<T> {
+    /// A value was received
+    Value(T),
+    /// The receiver fell behind, and the number of messages were lost.  The next call receives the oldest message which is still buffered.
+    Lagged(u64),
+    /// The channel is closed, and no more values will be received
+    Closed,
+}
+
 /// The sender half of an asynchronous channel, which may be bounded/unbounded, mpsc/broadcast/oneshot, etc.
 ///
 /// This trait provides a consistent interface for all async senders, which makes your app code
 /// very robust to channel changes on the bus.  It also allows `impl Sender<ExampleMessage>` in your associated function signatures.
+///
+/// Senders can also be used from synchronous code with `try_send`, which never waits:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::error::{TryRecvError, TrySendError};
+/// use lifeline::{Receiver, Sender};
+/// use tokio::sync::mpsc;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug, PartialEq)]
+/// struct ExampleMessage {}
+/// impl Message<ExampleBus> for ExampleMessage {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// fn report(tx: &mut impl Sender<ExampleMessage>) -> bool {
+///     match tx.try_send(ExampleMessage {}) {
+///         Ok(()) => true,
+///         Err(TrySendError::Full(_)) => false,
+///         Err(TrySendError::Closed(_)) => false,
+///     }
+/// }
+///
+/// fn poll(rx: &mut impl Receiver<ExampleMessage>) -> Result<ExampleMessage, TryRecvError> {
+///     rx.try_recv()
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     bus.capacity::<ExampleMessage>(1)?;
+///
+///     let mut tx = bus.tx::<ExampleMessage>()?;
+///     let mut rx = bus.rx::<ExampleMessage>()?;
+///
+///     assert_eq!(Err(TryRecvError::Empty), poll(&mut rx));
+///     assert!(report(&mut tx));
+///     assert!(!report(&mut tx));
+///     assert_eq!(Ok(ExampleMessage {}), poll(&mut rx));
+///     Ok(())
+/// }
+/// ```
 #[async_trait]
 pub trait Sender<T: Debug> {
     async fn send(&mut self, value: T) -> Result<(), SendError<T>>;
+
+    /// Attempts to send the value without waiting.
+    ///
+    /// If the channel is full, returns `TrySendError::Full(value)`.  If the channel is closed, returns `TrySendError::Closed(value)`.
+    fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>>;
+
+    /// Sends the value, waiting at most `timeout` for channel capacity.
+    ///
+    /// If the timeout elapses, returns `SendError::Timeout(value)`.  If the channel is closed, returns `SendError::Return(value)`.
+    ///
+    /// Channels which can wait for capacity without consuming the message override it, and wake as soon as capacity is available.
+    /// This includes the tokio mpsc, postage, and lifeline-native channels.  Channels which never wait send immediately.
+    ///
+    /// The default implementation retries `try_send` with a backoff, so the message is never lost when the timeout elapses.
+    /// It's used by channels whose send futures drop the message if they are cancelled (the async-std and flume channels).
+    async fn send_timeout(&mut self, value: T, timeout: Duration) -> Result<(), SendError<T>>
+    where
+        T: Send + 'async_trait,
+        Self: Send,
+    {
+        let deadline = Instant::now() + timeout;
+        let mut backoff = Duration::from_millis(1);
+        let mut value = value;
+
+        loop {
+            match self.try_send(value) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Closed(returned)) => return Err(SendError::Return(returned)),
+                Err(TrySendError::Full(returned)) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(SendError::Timeout(returned));
+                    }
+
+                    value = returned;
+                    sleep(backoff.min(deadline - now)).await;
+                    backoff = (backoff * 2).min(SEND_TIMEOUT_MAX_BACKOFF);
+                }
+            }
+        }
+    }
+
+    /// Converts the sender into a [Sink](./struct.SenderSink.html), which can be used with `SinkExt` combinators and `StreamExt::forward`.
+    fn into_sink(self) -> SenderSink<Self, T>
+    where
+        Self: Sized + Send + 'static,
+        T: Send + 'static,
+    {
+        SenderSink::new(self)
+    }
+
+    /// Returns a sender of `U`, which converts each message with the function and sends it on this sender.
+    ///
+    /// The function borrows the message, so the original message can be returned if the send fails.
+    /// The name avoids a clash with `SinkExt::with`, which is in scope for tokio and postage senders.
+    fn map_send<U, F>(self, f: F) -> WithSender<Self, F, T>
+    where
+        Self: Sized,
+        F: FnMut(&U) -> T,
+    {
+        WithSender::new(self, f)
+    }
+}
+
+/// A [Sender](./trait.Sender.html) which can send through a shared reference.
+///
+/// This is implemented for channels which can send from `&self` (tokio, async-std, and flume senders).
+/// Concurrent futures within one task can share the sender, without cloning it or wrapping it in a Mutex.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::SharedSender;
+/// use tokio::sync::mpsc;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// struct ExampleProgress(usize);
+/// impl Message<ExampleBus> for ExampleProgress {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// async fn download(part: usize, tx: &impl SharedSender<ExampleProgress>) -> anyhow::Result<()> {
+///     tx.send_shared(ExampleProgress(part)).await?;
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     let tx = bus.tx::<ExampleProgress>()?;
+///     let mut rx = bus.rx::<ExampleProgress>()?;
+///
+///     let (first, second) = tokio::join!(download(1, &tx), download(2, &tx));
+///     first?;
+///     second?;
+///
+///     assert!(rx.recv().await.is_some());
+///     assert!(rx.recv().await.is_some());
+///     Ok(())
+/// }
+/// ```
+#[async_trait]
+pub trait SharedSender<T: Debug>: Sender<T> {
+    /// Sends the value through a shared reference.  Has the same behavior as [Sender::send](./trait.Sender.html#tymethod.send).
+    async fn send_shared(&self, value: T) -> Result<(), SendError<T>>;
+
+    /// Attempts to send the value through a shared reference, without waiting.  Has the same behavior as [Sender::try_send](./trait.Sender.html#tymethod.try_send).
+    fn try_send_shared(&self, value: T) -> Result<(), TrySendError<T>>;
 }
 
 /// The receiver half of an asynchronous channel, which may be bounded/unbounded, mpsc/broadcast/oneshot, etc.
@@ -27,4 +255,298 @@ pub trait Sender<T: Debug> {
 #[async_trait]
 pub trait Receiver<T> {
     async fn recv(&mut self) -> Option<T>;
+
+    /// Attempts to receive a value without waiting.
+    ///
+    /// If no value is available, returns `TryRecvError::Empty`.  If the channel is empty and closed, returns `TryRecvError::Closed`.
+    ///
+    /// The default implementation polls `recv` once.  Channels which provide a non-blocking receive should override it.
+    fn try_recv(&mut self) -> Result<T, TryRecvError>
+    where
+        T: Send,
+    {
+        match self.recv().now_or_never() {
+            Some(Some(value)) => Ok(value),
+            Some(None) => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Receives a value, or reports that the receiver fell behind and lost messages.
+    ///
+    /// `recv` skips lost messages (or applies the bus [LagPolicy](./lag/enum.LagPolicy.html), for lag-tracked channels).
+    /// `recv_lossy` returns `Recv::Lagged(n)` instead, so the caller can recover.  Lag is reported by broadcast channels which drop the oldest messages.
+    /// The default implementation never reports lag.
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::prelude::*;
+    /// use lifeline::Recv;
+    /// use tokio::sync::broadcast;
+    ///
+    /// lifeline_bus!(pub struct ExampleBus);
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// struct ExampleEvent(usize);
+    /// impl Message<ExampleBus> for ExampleEvent {
+    ///     type Channel = broadcast::Sender<Self>;
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     use lifeline::Receiver;
+    ///
+    ///     let bus = ExampleBus::default();
+    ///     bus.capacity::<ExampleEvent>(2)?;
+    ///
+    ///     let mut rx = bus.rx::<ExampleEvent>()?;
+    ///     let tx = bus.tx::<ExampleEvent>()?;
+    ///     for i in 0..3 {
+    ///         tx.send(ExampleEvent(i))?;
+    ///     }
+    ///
+    ///     assert_eq!(Recv::Lagged(1), rx.recv_lossy().await);
+    ///     assert_eq!(Recv::Value(ExampleEvent(1)), rx.recv_lossy().await);
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn recv_lossy(&mut self) -> Recv<T>
+    where
+        T: Send + 'async_trait,
+        Self: Send,
+    {
+        match self.recv().await {
+            Some(value) => Recv::Value(value),
+            None => Recv::Closed,
+        }
+    }
+
+    /// Waits for a value, and then receives up to `limit` values which are available without waiting.  Values are appended to the buffer.
+    ///
+    /// Returns the number of values received.  If the channel is closed (or `limit` is zero), returns 0.
+    ///
+    /// The default implementation waits with `recv`, and then calls `try_recv` until the channel is empty.
+    async fn recv_many(&mut self, buffer: &mut Vec<T>, limit: usize) -> usize
+    where
+        T: Send + 'async_trait,
+        Self: Send,
+    {
+        if limit == 0 {
+            return 0;
+        }
+
+        match self.recv().await {
+            Some(value) => buffer.push(value),
+            None => return 0,
+        }
+
+        let mut received = 1;
+        while received < limit {
+            match self.try_recv() {
+                Ok(value) => {
+                    buffer.push(value);
+                    received += 1;
+                }
+                Err(_) => break,
+            }
+        }
+
+        received
+    }
+
+    /// Waits for a value, and then collects a batch of up to `max` values.  After the first value arrives,
+    /// the receiver waits at most `linger` for more values to fill the batch.
+    ///
+    /// If the channel is closed before any values are received, returns an empty batch.
+    /// When the linger elapses, the pending receive is cancelled, so `recv` must be cancellation safe (as it is for the channels supported by lifeline).
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::prelude::*;
+    /// use std::time::Duration;
+    /// use tokio::sync::mpsc;
+    ///
+    /// lifeline_bus!(pub struct ExampleBus);
+    ///
+    /// #[derive(Debug)]
+    /// struct ExampleRow(usize);
+    /// impl Message<ExampleBus> for ExampleRow {
+    ///     type Channel = mpsc::Sender<Self>;
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     use lifeline::Receiver;
+    ///
+    ///     let bus = ExampleBus::default();
+    ///     let tx = bus.tx::<ExampleRow>()?;
+    ///     let mut rx = bus.rx::<ExampleRow>()?;
+    ///
+    ///     for i in 0..5 {
+    ///         tx.send(ExampleRow(i)).await?;
+    ///     }
+    ///
+    ///     // a database writer can insert rows in batches
+    ///     let batch = rx.recv_batch(4, Duration::from_millis(10)).await;
+    ///     assert_eq!(4, batch.len());
+    ///
+    ///     let batch = rx.recv_batch(4, Duration::from_millis(10)).await;
+    ///     assert_eq!(1, batch.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn recv_batch(&mut self, max: usize, linger: Duration) -> Vec<T>
+    where
+        T: Send + 'async_trait,
+        Self: Send,
+    {
+        let mut batch = Vec::new();
+        if self.recv_many(&mut batch, max).await == 0 {
+            return batch;
+        }
+
+        let deadline = Instant::now() + linger;
+        while batch.len() < max {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+
+            let limit = max - batch.len();
+            let received = {
+                let more = self.recv_many(&mut batch, limit);
+                let timer = sleep(deadline - now);
+                pin_mut!(more, timer);
+
+                match select(more, timer).await {
+                    Either::Left((received, _)) => received,
+                    Either::Right(_) => 0,
+                }
+            };
+
+            if received == 0 {
+                break;
+            }
+        }
+
+        batch
+    }
+
+    /// Converts the receiver into a [Stream](./struct.ReceiverStream.html), which can be used with `StreamExt` combinators and `select!`.
+    fn into_stream(self) -> ReceiverStream<Self, T>
+    where
+        Self: Sized + Send + 'static,
+        T: Send + 'static,
+    {
+        ReceiverStream::new(self)
+    }
+
+    /// Returns a receiver which converts each message with the function.
+    ///
+    /// The receiver adapters are suffixed with `_recv`, so they don't clash with the `StreamExt` combinators of receivers which are also streams.
+    fn map_recv<U, F>(self, f: F) -> MapReceiver<Self, F, T>
+    where
+        Self: Sized,
+        F: FnMut(T) -> U,
+    {
+        MapReceiver::new(self, f)
+    }
+
+    /// Returns a receiver which skips messages which don't match the predicate.
+    fn filter_recv<F>(self, f: F) -> FilterReceiver<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&T) -> bool,
+    {
+        FilterReceiver::new(self, f)
+    }
+
+    /// Returns a receiver which converts each message with the function, and skips messages which convert to `None`.
+    fn filter_map_recv<U, F>(self, f: F) -> FilterMapReceiver<Self, F, T>
+    where
+        Self: Sized,
+        F: FnMut(T) -> Option<U>,
+    {
+        FilterMapReceiver::new(self, f)
+    }
+}
+
+#[cfg(all(test, feature = "tokio-channels"))]
+mod tests {
+    use crate::{Channel, PrioritySender, Receiver};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn default_recv_many_respects_limit() {
+        // the priority receiver uses the default recv_many
+        let (tx, mut rx) = PrioritySender::channel(8);
+        for n in 0..5usize {
+            tx.send_with_priority(n, 0).await.unwrap();
+        }
+
+        let mut buffer = Vec::new();
+        assert_eq!(0, rx.recv_many(&mut buffer, 0).await);
+        assert_eq!(3, rx.recv_many(&mut buffer, 3).await);
+        assert_eq!(2, rx.recv_many(&mut buffer, 3).await);
+        assert_eq!(vec![0, 1, 2, 3, 4], buffer);
+
+        drop(tx);
+        assert_eq!(0, rx.recv_many(&mut buffer, 3).await);
+    }
+
+    #[tokio::test]
+    async fn recv_many_returns_zero_when_closed() {
+        let (tx, mut rx) = mpsc::channel::<usize>(4);
+        tx.send(1).await.unwrap();
+        drop(tx);
+
+        let mut buffer = Vec::new();
+        assert_eq!(1, Receiver::recv_many(&mut rx, &mut buffer, 4).await);
+        assert_eq!(0, Receiver::recv_many(&mut rx, &mut buffer, 4).await);
+        assert_eq!(vec![1], buffer);
+    }
+
+    #[tokio::test]
+    async fn recv_batch_is_empty_when_closed() {
+        let (tx, mut rx) = mpsc::channel::<usize>(4);
+        drop(tx);
+
+        let batch = Receiver::recv_batch(&mut rx, 4, Duration::from_secs(1)).await;
+        assert!(batch.is_empty());
+    }
+
+    #[tokio::test]
+    async fn recv_batch_waits_for_more_values() {
+        let (tx, mut rx) = mpsc::channel::<usize>(4);
+        tx.send(1).await.unwrap();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            tx.send(2).await.unwrap();
+        });
+
+        // the batch is full before the linger elapses, so it returns early
+        let batch = tokio::time::timeout(
+            Duration::from_secs(5),
+            Receiver::recv_batch(&mut rx, 2, Duration::from_secs(10)),
+        )
+        .await
+        .expect("the batch returns once it's full");
+        assert_eq!(vec![1, 2], batch);
+    }
+
+    #[tokio::test]
+    async fn recv_batch_returns_partial_batch_after_linger() {
+        let (tx, mut rx) = mpsc::channel::<usize>(4);
+        tx.send(1).await.unwrap();
+
+        let batch = Receiver::recv_batch(&mut rx, 4, Duration::from_millis(5)).await;
+        assert_eq!(vec![1], batch);
+
+        // the sender is still open, and values sent later are received by the next batch
+        tx.send(2).await.unwrap();
+        let batch = Receiver::recv_batch(&mut rx, 4, Duration::from_millis(5)).await;
+        assert_eq!(vec![2], batch);
+    }
 }
@@ -1,11 +1,44 @@
 use super::Channel;
+use crate::error::{TryRecvError as LifelineTryRecvError, TrySendError as LifelineTrySendError};
+use crate::{
+    backpressure::SingleConsumer, channel::lifeline::send_until, impl_channel_clone,
+    impl_channel_take,
+};
 use crate::{error::SendError as LifelineSendError, impl_storage_clone, impl_storage_take};
-use crate::{impl_channel_clone, impl_channel_take};
 use async_trait::async_trait;
-use postage::sink::Sink;
-use postage::stream::Stream;
+use postage::sink::{self, PollSend, Sink};
+use postage::stream::{self, Stream};
 use postage::{barrier, broadcast, dispatch, mpsc, oneshot, watch};
-use std::fmt::Debug;
+use std::{fmt::Debug, pin::Pin, task::Poll, time::Duration};
+
+/// Sends the value on the postage sink, waiting at most `timeout` for capacity.
+///
+/// postage sinks return the value if they can't accept it, so the value is returned if the timeout elapses.
+async fn sink_send_timeout<S>(
+    tx: &mut S,
+    value: S::Item,
+    timeout: Duration,
+) -> Result<(), LifelineSendError<S::Item>>
+where
+    S: Sink + Unpin,
+    S::Item: Debug,
+{
+    send_until(value, Some(timeout), |cx, slot| {
+        let value = slot
+            .take()
+            .expect("the value is held until the send completes");
+
+        match Pin::new(&mut *tx).poll_send(&mut cx.into(), value) {
+            PollSend::Ready => Poll::Ready(Ok(())),
+            PollSend::Pending(value) => {
+                *slot = Some(value);
+                Poll::Pending
+            }
+            PollSend::Rejected(value) => Poll::Ready(Err(LifelineSendError::Return(value))),
+        }
+    })
+    .await
+}
 
 // barrier
 impl Channel for barrier::Sender {
@@ -24,6 +57,33 @@ impl Channel for barrier::Sender {
 impl_storage_take!(barrier::Sender);
 impl_storage_clone!(barrier::Receiver);
 
+/// Sending on a barrier releases all receivers.  The barrier can only be released once, and a second send returns `SendError::Return(())`.
+#[async_trait]
+impl crate::Sender<()> for barrier::Sender {
+    async fn send(&mut self, value: ()) -> Result<(), LifelineSendError<()>> {
+        Sink::send(self, value)
+            .await
+            .map_err(|_| LifelineSendError::Return(()))
+    }
+
+    fn try_send(&mut self, value: ()) -> Result<(), LifelineTrySendError<()>> {
+        Sink::try_send(self, value).map_err(into_try_send_error)
+    }
+}
+
+/// Waits for the barrier to be released.  The barrier is released when the sender sends a value, or is dropped.
+/// Once the barrier is released, recv always returns `Some(())`.
+#[async_trait]
+impl crate::Receiver<()> for barrier::Receiver {
+    async fn recv(&mut self) -> Option<()> {
+        Stream::recv(self).await
+    }
+
+    fn try_recv(&mut self) -> Result<(), LifelineTryRecvError> {
+        Stream::try_recv(self).map_err(into_try_recv_error)
+    }
+}
+
 // broadcast
 impl<T: Clone + Send + 'static> Channel for broadcast::Sender<T> {
     type Tx = Self;
@@ -40,6 +100,10 @@ impl<T: Clone + Send + 'static> Channel for broadcast::Sender<T> {
     fn clone_rx(rx: &mut Option<Self::Rx>, tx: Option<&Self::Tx>) -> Option<Self::Rx> {
         rx.take().or_else(|| tx.map(|tx| tx.subscribe()))
     }
+
+    fn rx_available(rx: Option<&Self::Rx>, tx: Option<&Self::Tx>) -> bool {
+        rx.is_some() || tx.is_some()
+    }
 }
 
 impl_channel_clone!(broadcast::Sender<T>);
@@ -56,6 +120,18 @@ where
             .map(|_| ())
             .map_err(|err| LifelineSendError::Return(err.0))
     }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        Sink::try_send(self, value).map_err(into_try_send_error)
+    }
+
+    async fn send_timeout(
+        &mut self,
+        value: T,
+        timeout: Duration,
+    ) -> Result<(), LifelineSendError<T>> {
+        sink_send_timeout(self, value, timeout).await
+    }
 }
 
 #[async_trait]
@@ -66,6 +142,10 @@ where
     async fn recv(&mut self) -> Option<T> {
         Stream::recv(self).await
     }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        Stream::try_recv(self).map_err(into_try_recv_error)
+    }
 }
 
 // mpsc
@@ -82,6 +162,8 @@ impl<T: Send + 'static> Channel for mpsc::Sender<T> {
     }
 }
 
+impl<T: Send + 'static> SingleConsumer for mpsc::Sender<T> {}
+
 impl_channel_clone!(mpsc::Sender<T>);
 impl_channel_take!(mpsc::Receiver<T>);
 
@@ -95,6 +177,18 @@ where
             .await
             .map_err(|err| LifelineSendError::Return(err.0))
     }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        Sink::try_send(self, value).map_err(into_try_send_error)
+    }
+
+    async fn send_timeout(
+        &mut self,
+        value: T,
+        timeout: Duration,
+    ) -> Result<(), LifelineSendError<T>> {
+        sink_send_timeout(self, value, timeout).await
+    }
 }
 
 #[async_trait]
@@ -105,6 +199,10 @@ where
     async fn recv(&mut self) -> Option<T> {
         Stream::recv(self).await
     }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        Stream::try_recv(self).map_err(into_try_recv_error)
+    }
 }
 
 // dispatch
@@ -134,6 +232,18 @@ where
             .await
             .map_err(|err| LifelineSendError::Return(err.0))
     }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        Sink::try_send(self, value).map_err(into_try_send_error)
+    }
+
+    async fn send_timeout(
+        &mut self,
+        value: T,
+        timeout: Duration,
+    ) -> Result<(), LifelineSendError<T>> {
+        sink_send_timeout(self, value, timeout).await
+    }
 }
 
 #[async_trait]
@@ -144,6 +254,10 @@ where
     async fn recv(&mut self) -> Option<T> {
         Stream::recv(self).await
     }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        Stream::try_recv(self).map_err(into_try_recv_error)
+    }
 }
 
 // oneshot
@@ -163,6 +277,38 @@ impl<T: Send + 'static> Channel for oneshot::Sender<T> {
 impl_channel_take!(oneshot::Sender<T>);
 impl_channel_take!(oneshot::Receiver<T>);
 
+/// Sends the value on the oneshot channel.  A second send returns `SendError::Return(value)`.
+#[async_trait]
+impl<T> crate::Sender<T> for oneshot::Sender<T>
+where
+    T: Debug + Send,
+{
+    async fn send(&mut self, value: T) -> Result<(), LifelineSendError<T>> {
+        Sink::send(self, value)
+            .await
+            .map_err(|err| LifelineSendError::Return(err.0))
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        Sink::try_send(self, value).map_err(into_try_send_error)
+    }
+}
+
+/// Receives the value from the oneshot channel.  Once the value has been received, recv returns None.
+#[async_trait]
+impl<T> crate::Receiver<T> for oneshot::Receiver<T>
+where
+    T: Debug + Send,
+{
+    async fn recv(&mut self) -> Option<T> {
+        Stream::recv(self).await
+    }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        Stream::try_recv(self).map_err(into_try_recv_error)
+    }
+}
+
 impl<T> Channel for watch::Sender<T>
 where
     T: Default + Clone + Send + Sync + 'static,
@@ -192,6 +338,10 @@ where
             .await
             .map_err(|_| LifelineSendError::Closed)
     }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        Sink::try_send(self, value).map_err(into_try_send_error)
+    }
 }
 
 #[async_trait]
@@ -202,4 +352,153 @@ where
     async fn recv(&mut self) -> Option<T> {
         Stream::recv(self).await
     }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        Stream::try_recv(self).map_err(into_try_recv_error)
+    }
+}
+
+fn into_try_send_error<T: Debug>(err: sink::TrySendError<T>) -> LifelineTrySendError<T> {
+    match err {
+        sink::TrySendError::Pending(value) => LifelineTrySendError::Full(value),
+        sink::TrySendError::Rejected(value) => LifelineTrySendError::Closed(value),
+    }
+}
+
+fn into_try_recv_error(err: stream::TryRecvError) -> LifelineTryRecvError {
+    match err {
+        stream::TryRecvError::Pending => LifelineTryRecvError::Empty,
+        stream::TryRecvError::Closed => LifelineTryRecvError::Closed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::{SendError, TakeChannelError, TryRecvError, TrySendError},
+        lifeline_bus, Bus, Message, Receiver, Sender,
+    };
+    use postage::{barrier, mpsc, oneshot};
+    use std::time::Duration;
+
+    lifeline_bus!(struct PostageBus);
+
+    #[derive(Debug, PartialEq)]
+    struct Queued(usize);
+
+    impl Message<PostageBus> for Queued {
+        type Channel = mpsc::Sender<Self>;
+    }
+
+    #[derive(Debug)]
+    struct Release;
+
+    impl Message<PostageBus> for Release {
+        type Channel = barrier::Sender;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Shutdown;
+
+    impl Message<PostageBus> for Shutdown {
+        type Channel = oneshot::Sender<Self>;
+    }
+
+    #[tokio::test]
+    async fn barrier_releases_all_receivers() {
+        let bus = PostageBus::default();
+        let mut tx = bus.tx::<Release>().unwrap();
+        let mut rx_1 = bus.rx::<Release>().unwrap();
+        let mut rx_2 = bus.rx::<Release>().unwrap();
+
+        assert_eq!(Err(TryRecvError::Empty), Receiver::try_recv(&mut rx_1));
+
+        Sender::send(&mut tx, ()).await.unwrap();
+        assert_eq!(Err(SendError::Return(())), Sender::send(&mut tx, ()).await);
+
+        assert_eq!(Some(()), Receiver::recv(&mut rx_1).await);
+        assert_eq!(Some(()), Receiver::recv(&mut rx_1).await);
+        assert_eq!(Ok(()), Receiver::try_recv(&mut rx_2));
+    }
+
+    #[tokio::test]
+    async fn barrier_released_when_tx_dropped() {
+        let bus = PostageBus::default();
+        let tx = bus.tx::<Release>().unwrap();
+        let mut rx = bus.rx::<Release>().unwrap();
+
+        assert!(matches!(
+            bus.tx::<Release>(),
+            Err(TakeChannelError::AlreadyTaken(_))
+        ));
+
+        drop(tx);
+        assert_eq!(Some(()), Receiver::recv(&mut rx).await);
+    }
+
+    #[tokio::test]
+    async fn oneshot_sends_once() {
+        let bus = PostageBus::default();
+        let mut tx = bus.tx::<Shutdown>().unwrap();
+        let mut rx = bus.rx::<Shutdown>().unwrap();
+
+        assert_eq!(Err(TryRecvError::Empty), Receiver::try_recv(&mut rx));
+
+        Sender::send(&mut tx, Shutdown).await.unwrap();
+        assert_eq!(
+            Err(SendError::Return(Shutdown)),
+            Sender::send(&mut tx, Shutdown).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(Shutdown)),
+            Sender::try_send(&mut tx, Shutdown)
+        );
+
+        assert_eq!(Some(Shutdown), Receiver::recv(&mut rx).await);
+        assert_eq!(None, Receiver::recv(&mut rx).await);
+    }
+
+    #[tokio::test]
+    async fn oneshot_closed_when_rx_dropped() {
+        let bus = PostageBus::default();
+        let mut tx = bus.tx::<Shutdown>().unwrap();
+        drop(bus.rx::<Shutdown>().unwrap());
+
+        assert!(matches!(
+            bus.rx::<Shutdown>(),
+            Err(TakeChannelError::AlreadyTaken(_))
+        ));
+        assert_eq!(
+            Err(SendError::Return(Shutdown)),
+            Sender::send(&mut tx, Shutdown).await
+        );
+    }
+
+    #[tokio::test]
+    async fn mpsc_send_timeout_returns_message() {
+        let bus = PostageBus::default();
+        bus.capacity::<Queued>(1).unwrap();
+
+        let mut tx = bus.tx::<Queued>().unwrap();
+        let mut rx = bus.rx::<Queued>().unwrap();
+
+        while Sender::try_send(&mut tx, Queued(0)).is_ok() {}
+
+        let timeout = Duration::from_millis(5);
+        assert_eq!(
+            Err(SendError::Timeout(Queued(1))),
+            Sender::send_timeout(&mut tx, Queued(1), timeout).await
+        );
+
+        assert_eq!(Some(Queued(0)), Receiver::recv(&mut rx).await);
+        Sender::send_timeout(&mut tx, Queued(2), timeout)
+            .await
+            .unwrap();
+
+        drop(rx);
+        assert_eq!(
+            Err(SendError::Return(Queued(3))),
+            Sender::send_timeout(&mut tx, Queued(3), timeout).await
+        );
+    }
 }
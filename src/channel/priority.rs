@@ -0,0 +1,757 @@
+use super::Channel;
+use crate::error::{SendError, TryRecvError, TrySendError};
+use crate::{
+    channel::{
+        lifeline::send_until,
+        shared::{Shared, State},
+    },
+    impl_channel_clone, impl_channel_take,
+    intercept::{warn_timeout, Action, Interceptors},
+    spawn::sleep,
+};
+use async_trait::async_trait;
+use futures_util::future::poll_fn;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Debug,
+    task::{Poll, Waker},
+    time::Duration,
+};
+
+/// Declares the priority of a message carried by a [PrioritySender](./struct.PrioritySender.html).
+///
+/// Messages with a higher priority are received first.  Within one priority, messages are received in the order they were sent.
+/// The default priority is `0`, so bulk messages can use an empty implementation.
+pub trait Priority {
+    /// Returns the priority of the message.  Higher values are received first.
+    fn priority(&self) -> u8 {
+        0
+    }
+}
+
+/// A lifeline-native priority channel, which lets control messages overtake bulk messages on the same consumer.
+///
+/// The message type declares its priority with the [Priority](./trait.Priority.html) trait,
+/// or the priority can be provided with [send_with_priority](#method.send_with_priority).
+/// The capacity is shared by all priorities, and is configured with [Bus::capacity](./trait.Bus.html#tymethod.capacity).
+/// When the channel is full, senders wait for capacity.
+///
+/// The sender is clonable, and the receiver is taken.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::{Priority, PrioritySender, Receiver, Sender};
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug, PartialEq)]
+/// enum ExampleWork {
+///     Chunk(usize),
+///     Shutdown,
+/// }
+///
+/// impl Priority for ExampleWork {
+///     fn priority(&self) -> u8 {
+///         match self {
+///             ExampleWork::Chunk(_) => 0,
+///             ExampleWork::Shutdown => 1,
+///         }
+///     }
+/// }
+///
+/// impl Message<ExampleBus> for ExampleWork {
+///     type Channel = PrioritySender<Self>;
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     bus.capacity::<ExampleWork>(8)?;
+///
+///     let mut tx = bus.tx::<ExampleWork>()?;
+///     let mut rx = bus.rx::<ExampleWork>()?;
+///
+///     tx.send(ExampleWork::Chunk(1)).await?;
+///     tx.send(ExampleWork::Chunk(2)).await?;
+///     tx.send(ExampleWork::Shutdown).await?;
+///
+///     assert_eq!(Some(ExampleWork::Shutdown), rx.recv().await);
+///     assert_eq!(Some(ExampleWork::Chunk(1)), rx.recv().await);
+///     assert_eq!(Some(ExampleWork::Chunk(2)), rx.recv().await);
+///     Ok(())
+/// }
+/// ```
+pub struct PrioritySender<T> {
+    shared: Shared<Queue<T>>,
+    interceptors: Interceptors,
+}
+
+/// The receiver of a [PrioritySender](./struct.PrioritySender.html).  Receives the oldest message with the highest priority.
+pub struct PriorityReceiver<T> {
+    shared: Shared<Queue<T>>,
+}
+
+struct Queue<T> {
+    queues: BTreeMap<u8, VecDeque<T>>,
+    len: usize,
+    capacity: usize,
+    /// The senders which are waiting for capacity, in the order they started waiting, with the id of their send call.
+    /// Senders keep their position when they are woken, and are removed when the send completes or is cancelled.
+    send_wakers: VecDeque<(usize, Waker)>,
+    /// The number of free slots which are reserved for woken senders.  These are the first senders in `send_wakers`.
+    reserved: usize,
+    next_waiter: usize,
+}
+
+impl<T> Queue<T> {
+    fn push(&mut self, value: T, priority: u8) {
+        self.queues.entry(priority).or_default().push_back(value);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let mut entry = self.queues.last_entry()?;
+        let value = entry.get_mut().pop_front();
+
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+
+        self.len -= 1;
+
+        // one slot was freed, so one sender is woken
+        self.wake_sender();
+
+        value
+    }
+
+    /// Returns true if a new message can be pushed without taking a slot which is reserved for a woken sender
+    fn has_capacity(&self) -> bool {
+        self.len + self.reserved < self.capacity
+    }
+
+    /// Wakes the sender which has waited the longest, and reserves a free slot for it.
+    /// Senders which start waiting later can't take the slot.
+    fn wake_sender(&mut self) {
+        if !self.has_capacity() {
+            return;
+        }
+
+        if let Some((_id, waker)) = self.send_wakers.get(self.reserved) {
+            waker.wake_by_ref();
+            self.reserved += 1;
+        }
+    }
+
+    /// Removes the waker of a send call.  Returns true if the send had been woken, and held a reserved slot.
+    fn remove_waiter(&mut self, id: usize) -> bool {
+        let index = match self.send_wakers.iter().position(|(i, _)| *i == id) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        self.send_wakers.remove(index);
+
+        let reserved = index < self.reserved;
+        if reserved {
+            self.reserved -= 1;
+        }
+
+        reserved
+    }
+}
+
+/// The waker registration of a send call which is waiting for capacity.
+///
+/// Each send call keeps one waker in the queue.  If the send is cancelled after it was woken, the wakeup is passed to the next sender.
+struct SendWaiter<'a, T> {
+    shared: &'a Shared<Queue<T>>,
+    id: Option<usize>,
+}
+
+impl<'a, T> SendWaiter<'a, T> {
+    fn new(shared: &'a Shared<Queue<T>>) -> Self {
+        Self { shared, id: None }
+    }
+
+    /// Stores the waker, replacing the waker from a previous poll of the same send call
+    fn register(&mut self, queue: &mut Queue<T>, waker: &Waker) {
+        let id = self.id;
+        let registered = id.and_then(|id| queue.send_wakers.iter_mut().find(|(i, _)| *i == id));
+
+        match registered {
+            Some((_id, registered)) => {
+                if !registered.will_wake(waker) {
+                    *registered = waker.clone();
+                }
+            }
+            None => {
+                let id = queue.next_waiter;
+                queue.next_waiter = queue.next_waiter.wrapping_add(1);
+                queue.send_wakers.push_back((id, waker.clone()));
+                self.id = Some(id);
+            }
+        }
+    }
+
+    /// Returns true if the send call was woken, and holds a reserved slot
+    fn is_reserved(&self, queue: &Queue<T>) -> bool {
+        let id = match self.id {
+            Some(id) => id,
+            None => return false,
+        };
+
+        queue
+            .send_wakers
+            .iter()
+            .take(queue.reserved)
+            .any(|(i, _)| *i == id)
+    }
+
+    /// Removes the waker (and releases the reserved slot), after the send call completes
+    fn complete(&mut self, queue: &mut Queue<T>) {
+        if let Some(id) = self.id.take() {
+            queue.remove_waiter(id);
+        }
+    }
+}
+
+impl<'a, T> Drop for SendWaiter<'a, T> {
+    fn drop(&mut self) {
+        let id = match self.id {
+            Some(id) => id,
+            None => return,
+        };
+
+        let mut state = self.shared.lock();
+        let queue = &mut state.queue;
+
+        // the send was woken for a free slot, but was cancelled before it could use it
+        if queue.remove_waiter(id) {
+            queue.wake_sender();
+        }
+    }
+}
+
+impl<T> PrioritySender<T> {
+    /// Sends the value with the provided priority, waiting for capacity if the channel is full.
+    /// If the receiver has been dropped, returns `SendError::Return(value)`.
+    ///
+    /// If the bus has a default send timeout, returns `SendError::Timeout(value)` when the timeout elapses.
+    pub async fn send_with_priority(&self, value: T, priority: u8) -> Result<(), SendError<T>>
+    where
+        T: Debug,
+    {
+        let timeout = self.interceptors.send_timeout();
+        self.send_inner(value, priority, timeout).await
+    }
+
+    /// Sends the value with the provided priority, waiting at most `timeout` for capacity.
+    /// If the timeout elapses, returns `SendError::Timeout(value)`.
+    pub async fn send_timeout_with_priority(
+        &self,
+        value: T,
+        priority: u8,
+        timeout: Duration,
+    ) -> Result<(), SendError<T>>
+    where
+        T: Debug,
+    {
+        self.send_inner(value, priority, Some(timeout)).await
+    }
+
+    async fn send_inner(
+        &self,
+        value: T,
+        priority: u8,
+        timeout: Option<Duration>,
+    ) -> Result<(), SendError<T>>
+    where
+        T: Debug,
+    {
+        match self.interceptors.intercept(&value) {
+            Action::Pass => {}
+            Action::Drop => return Ok(()),
+            Action::Delay(duration) => sleep(duration).await,
+        }
+
+        let mut waiter = SendWaiter::new(&self.shared);
+
+        let result = send_until(value, timeout, |cx, slot| {
+            let mut state = self.shared.lock();
+
+            if !state.receiver {
+                waiter.complete(&mut state.queue);
+                let value = slot
+                    .take()
+                    .expect("the value is held until the send completes");
+                return Poll::Ready(Err(SendError::Return(value)));
+            }
+
+            if waiter.is_reserved(&state.queue) || state.queue.has_capacity() {
+                waiter.complete(&mut state.queue);
+                let value = slot
+                    .take()
+                    .expect("the value is held until the send completes");
+                push(&mut state, value, priority);
+                return Poll::Ready(Ok(()));
+            }
+
+            waiter.register(&mut state.queue, cx.waker());
+            Poll::Pending
+        })
+        .await;
+
+        if let Some(timeout) = timeout {
+            warn_timeout(&result, timeout);
+        }
+
+        result
+    }
+
+    /// Attempts to send the value with the provided priority, without waiting.
+    pub fn try_send_with_priority(&self, value: T, priority: u8) -> Result<(), TrySendError<T>>
+    where
+        T: Debug,
+    {
+        // try_send can't wait, so delays requested by interceptors are skipped
+        if let Action::Drop = self.interceptors.intercept(&value) {
+            return Ok(());
+        }
+
+        let mut state = self.shared.lock();
+
+        if !state.receiver {
+            return Err(TrySendError::Closed(value));
+        }
+
+        if !state.queue.has_capacity() {
+            return Err(TrySendError::Full(value));
+        }
+
+        push(&mut state, value, priority);
+        Ok(())
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.len
+    }
+
+    /// Returns true if the channel contains no messages.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the receiver has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+}
+
+/// Pushes the value, and wakes the receiver
+fn push<T>(state: &mut State<Queue<T>>, value: T, priority: u8) {
+    state.queue.push(value, priority);
+    state.wake_receiver();
+}
+
+impl<T> Clone for PrioritySender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone_sender(),
+            interceptors: self.interceptors.clone(),
+        }
+    }
+}
+
+impl<T> Drop for PrioritySender<T> {
+    fn drop(&mut self) {
+        self.shared.drop_sender();
+    }
+}
+
+impl<T> Debug for PrioritySender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrioritySender")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T> PriorityReceiver<T> {
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.len
+    }
+
+    /// Returns true if the channel contains no messages.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for PriorityReceiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.drop_receiver();
+        let queue = &mut state.queue;
+        queue.queues.clear();
+        queue.len = 0;
+        queue.reserved = 0;
+
+        // all waiting senders are woken, and return their messages
+        for (_id, waker) in queue.send_wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Debug for PriorityReceiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PriorityReceiver")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T: Send + 'static> Channel for PrioritySender<T> {
+    type Tx = Self;
+    type Rx = PriorityReceiver<T>;
+
+    fn channel(capacity: usize) -> (Self::Tx, Self::Rx) {
+        let (tx, rx) = Shared::channel(Queue {
+            queues: BTreeMap::new(),
+            len: 0,
+            capacity: capacity.max(1),
+            send_wakers: VecDeque::new(),
+            reserved: 0,
+            next_waiter: 0,
+        });
+
+        let tx = PrioritySender {
+            shared: tx,
+            interceptors: Interceptors::default(),
+        };
+
+        (tx, PriorityReceiver { shared: rx })
+    }
+
+    fn default_capacity() -> usize {
+        16
+    }
+
+    fn is_closed(tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        tx.map(PrioritySender::is_closed).unwrap_or(false)
+    }
+
+    fn intercept(tx: &mut Self::Tx, interceptors: &Interceptors) {
+        tx.interceptors = interceptors.clone();
+    }
+}
+
+impl_channel_clone!(PrioritySender<T>);
+impl_channel_take!(PriorityReceiver<T>);
+
+#[async_trait]
+impl<T> crate::Sender<T> for PrioritySender<T>
+where
+    T: Priority + Debug + Send,
+{
+    async fn send(&mut self, value: T) -> Result<(), SendError<T>> {
+        let priority = value.priority();
+        self.send_with_priority(value, priority).await
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        let priority = value.priority();
+        self.try_send_with_priority(value, priority)
+    }
+
+    async fn send_timeout(&mut self, value: T, timeout: Duration) -> Result<(), SendError<T>> {
+        let priority = value.priority();
+        self.send_timeout_with_priority(value, priority, timeout)
+            .await
+    }
+}
+
+#[async_trait]
+impl<T> crate::SharedSender<T> for PrioritySender<T>
+where
+    T: Priority + Debug + Send,
+{
+    async fn send_shared(&self, value: T) -> Result<(), SendError<T>> {
+        let priority = value.priority();
+        self.send_with_priority(value, priority).await
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), TrySendError<T>> {
+        let priority = value.priority();
+        self.try_send_with_priority(value, priority)
+    }
+}
+
+#[async_trait]
+impl<T> crate::Receiver<T> for PriorityReceiver<T>
+where
+    T: Send,
+{
+    async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.shared.poll_recv(cx, Queue::pop)).await
+    }
+
+    fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.shared.try_recv(Queue::pop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PrioritySender;
+    use crate::{
+        error::{SendError, TrySendError},
+        Channel, Receiver,
+    };
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, Wake, Waker},
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn priorities_keep_fifo_order() {
+        let (tx, mut rx) = PrioritySender::channel(8);
+
+        tx.send_with_priority("bulk 1", 0).await.unwrap();
+        tx.send_with_priority("control 1", 2).await.unwrap();
+        tx.send_with_priority("bulk 2", 0).await.unwrap();
+        tx.send_with_priority("control 2", 2).await.unwrap();
+        tx.send_with_priority("reconfigure", 1).await.unwrap();
+        drop(tx);
+
+        let mut received = Vec::new();
+        while let Some(value) = rx.recv().await {
+            received.push(value);
+        }
+
+        assert_eq!(
+            vec!["control 1", "control 2", "reconfigure", "bulk 1", "bulk 2"],
+            received
+        );
+    }
+
+    /// A waker which records that it was woken
+    #[derive(Default)]
+    struct Flag(AtomicBool);
+
+    impl Flag {
+        fn woken(&self) -> bool {
+            self.0.swap(false, Ordering::SeqCst)
+        }
+    }
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn poll<F: Future + Unpin>(future: &mut F, flag: &Arc<Flag>) -> Poll<F::Output> {
+        let waker = Waker::from(flag.clone());
+        Pin::new(future).poll(&mut Context::from_waker(&waker))
+    }
+
+    #[tokio::test]
+    async fn full_sender_waits_for_capacity() {
+        let (tx, mut rx) = PrioritySender::channel(1);
+
+        tx.send_with_priority(1usize, 0).await.unwrap();
+        assert_eq!(Err(TrySendError::Full(2)), tx.try_send_with_priority(2, 0));
+
+        let flag = Arc::new(Flag::default());
+        let mut blocked = Box::pin(tx.send_with_priority(2, 0));
+        assert!(poll(&mut blocked, &flag).is_pending());
+
+        assert_eq!(Some(1), rx.recv().await);
+        assert!(flag.woken());
+        assert_eq!(Poll::Ready(Ok(())), poll(&mut blocked, &flag));
+
+        drop(blocked);
+        drop(tx);
+        assert_eq!(Some(2), rx.recv().await);
+        assert_eq!(None, rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn many_blocked_senders() {
+        let (tx, mut rx) = PrioritySender::channel(1);
+        tx.send_with_priority(0usize, 0).await.unwrap();
+
+        let senders: Vec<_> = (1..=16)
+            .map(|i| {
+                let tx = tx.clone();
+                tokio::spawn(async move { tx.send_with_priority(i, 0).await })
+            })
+            .collect();
+        drop(tx);
+
+        let mut received = Vec::new();
+        while let Some(value) = rx.recv().await {
+            received.push(value);
+        }
+
+        for sender in senders {
+            sender.await.unwrap().unwrap();
+        }
+
+        received.sort_unstable();
+        assert_eq!((0..=16).collect::<Vec<_>>(), received);
+    }
+
+    #[test]
+    fn one_sender_is_woken_per_slot() {
+        let (tx, mut rx) = PrioritySender::channel(1);
+        tx.try_send_with_priority(0usize, 0).unwrap();
+
+        let flags: Vec<_> = (0..3).map(|_| Arc::new(Flag::default())).collect();
+        let mut sends: Vec<_> = (1..=3)
+            .map(|i| Box::pin(tx.send_with_priority(i, 0)))
+            .collect();
+
+        // repeated polls keep one waker for each send
+        for _ in 0..3 {
+            for (send, flag) in sends.iter_mut().zip(&flags) {
+                assert!(poll(send, flag).is_pending());
+            }
+        }
+        assert_eq!(3, tx.shared.lock().queue.send_wakers.len());
+
+        assert_eq!(Ok(0), rx.try_recv());
+        let woken: Vec<_> = flags.iter().map(|flag| flag.woken()).collect();
+        assert_eq!(vec![true, false, false], woken);
+
+        assert_eq!(Poll::Ready(Ok(())), poll(&mut sends[0], &flags[0]));
+        assert_eq!(2, tx.shared.lock().queue.send_wakers.len());
+    }
+
+    #[test]
+    fn new_sender_cannot_take_reserved_slot() {
+        let (tx, mut rx) = PrioritySender::channel(1);
+        tx.try_send_with_priority(0usize, 0).unwrap();
+
+        let (waiting_flag, new_flag) = (Arc::new(Flag::default()), Arc::new(Flag::default()));
+        let mut waiting = Box::pin(tx.send_with_priority(1, 0));
+        assert!(poll(&mut waiting, &waiting_flag).is_pending());
+
+        assert_eq!(Ok(0), rx.try_recv());
+        assert!(waiting_flag.woken());
+
+        // the free slot is reserved for the woken sender
+        assert_eq!(Err(TrySendError::Full(2)), tx.try_send_with_priority(2, 0));
+        let mut new = Box::pin(tx.send_with_priority(3, 0));
+        assert!(poll(&mut new, &new_flag).is_pending());
+
+        assert_eq!(Poll::Ready(Ok(())), poll(&mut waiting, &waiting_flag));
+        assert_eq!(Ok(1), rx.try_recv());
+        assert!(new_flag.woken());
+        assert_eq!(Poll::Ready(Ok(())), poll(&mut new, &new_flag));
+        assert_eq!(Ok(3), rx.try_recv());
+    }
+
+    #[test]
+    fn woken_sender_keeps_position() {
+        let (tx, mut rx) = PrioritySender::channel(2);
+        tx.try_send_with_priority(0usize, 0).unwrap();
+        tx.try_send_with_priority(1, 0).unwrap();
+
+        let flags: Vec<_> = (0..3).map(|_| Arc::new(Flag::default())).collect();
+        let mut sends: Vec<_> = (2..=4)
+            .map(|i| Box::pin(tx.send_with_priority(i, 0)))
+            .collect();
+        for (send, flag) in sends.iter_mut().zip(&flags) {
+            assert!(poll(send, flag).is_pending());
+        }
+
+        // the later senders are polled again, and don't take the place of the woken sender
+        assert_eq!(Ok(0), rx.try_recv());
+        assert!(flags[0].woken());
+        assert!(poll(&mut sends[2], &flags[2]).is_pending());
+        assert!(poll(&mut sends[1], &flags[1]).is_pending());
+        assert_eq!(Poll::Ready(Ok(())), poll(&mut sends[0], &flags[0]));
+        assert_eq!(Ok(1), rx.try_recv());
+        let woken: Vec<_> = flags.iter().map(|flag| flag.woken()).collect();
+        assert_eq!(vec![false, true, false], woken);
+    }
+
+    #[test]
+    fn cancelled_sender_passes_wakeup() {
+        let (tx, mut rx) = PrioritySender::channel(1);
+        tx.try_send_with_priority(0usize, 0).unwrap();
+
+        let (first_flag, second_flag) = (Arc::new(Flag::default()), Arc::new(Flag::default()));
+        let mut first = Box::pin(tx.send_with_priority(1, 0));
+        let mut second = Box::pin(tx.send_with_priority(2, 0));
+        assert!(poll(&mut first, &first_flag).is_pending());
+        assert!(poll(&mut second, &second_flag).is_pending());
+
+        assert_eq!(Ok(0), rx.try_recv());
+        assert!(first_flag.woken());
+        assert!(!second_flag.woken());
+
+        // the first send is cancelled before it uses the free slot
+        drop(first);
+        assert!(second_flag.woken());
+        assert_eq!(Poll::Ready(Ok(())), poll(&mut second, &second_flag));
+        assert_eq!(Ok(2), rx.try_recv());
+    }
+
+    #[tokio::test]
+    async fn dropped_receiver_returns_message() {
+        let (tx, rx) = PrioritySender::channel(1);
+        drop(rx);
+
+        let result = tx.send_with_priority(1usize, 0).await;
+        assert_eq!(Some(1), result.unwrap_err().take_message());
+        assert!(tx.is_closed());
+    }
+
+    #[tokio::test]
+    async fn send_timeout_returns_message() {
+        let (tx, mut rx) = PrioritySender::channel(1);
+        tx.send_with_priority(1usize, 0).await.unwrap();
+
+        let result = tx
+            .send_timeout_with_priority(2, 0, Duration::from_millis(5))
+            .await;
+        assert_eq!(Err(SendError::Timeout(2)), result);
+
+        assert_eq!(Some(1), rx.recv().await);
+        tx.send_timeout_with_priority(3, 0, Duration::from_millis(5))
+            .await
+            .unwrap();
+        assert_eq!(Some(3), rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn send_timeout_wakes_on_capacity() {
+        let (tx, mut rx) = PrioritySender::channel(1);
+        tx.send_with_priority(1usize, 0).await.unwrap();
+
+        let blocked = tokio::spawn(async move {
+            tx.send_timeout_with_priority(2, 0, Duration::from_secs(10))
+                .await
+        });
+
+        assert_eq!(Some(1), rx.recv().await);
+        tokio::time::timeout(Duration::from_secs(1), blocked)
+            .await
+            .expect("the sender is woken when capacity is available")
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some(2), rx.recv().await);
+    }
+}
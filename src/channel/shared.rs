@@ -0,0 +1,115 @@
+use crate::error::TryRecvError;
+use std::{
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+};
+
+/// The state of a lifeline-native channel, which is shared by its senders and its receiver.
+///
+/// Tracks the number of senders, whether the receiver is alive, and the waker of the receiver.
+/// The queue holds the messages, and any other state of the channel implementation.
+pub(crate) struct Shared<Q> {
+    state: Arc<Mutex<State<Q>>>,
+}
+
+pub(crate) struct State<Q> {
+    pub queue: Q,
+    pub senders: usize,
+    pub receiver: bool,
+    recv_waker: Option<Waker>,
+}
+
+impl<Q> State<Q> {
+    /// Wakes the receiver, if it's waiting for a message
+    pub fn wake_receiver(&mut self) {
+        if let Some(waker) = self.recv_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<Q> Shared<Q> {
+    /// Creates the state of a new channel, and returns the handles for the first sender, and the receiver.
+    pub fn channel(queue: Q) -> (Self, Self) {
+        let state = Arc::new(Mutex::new(State {
+            queue,
+            senders: 1,
+            receiver: true,
+            recv_waker: None,
+        }));
+
+        let tx = Self {
+            state: state.clone(),
+        };
+
+        (tx, Self { state })
+    }
+
+    /// Locks the state.  If a thread panicked while holding the lock, the lock is recovered rather than poisoned.
+    pub fn lock(&self) -> MutexGuard<'_, State<Q>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns true if the receiver has been dropped.
+    pub fn is_closed(&self) -> bool {
+        !self.lock().receiver
+    }
+
+    /// Returns a handle for a cloned sender.
+    pub fn clone_sender(&self) -> Self {
+        self.lock().senders += 1;
+
+        Self {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Records a dropped sender.  When the last sender is dropped, the receiver is woken so it can observe the closed channel.
+    pub fn drop_sender(&self) {
+        let mut state = self.lock();
+        state.senders -= 1;
+
+        if state.senders == 0 {
+            state.wake_receiver();
+        }
+    }
+
+    /// Records the dropped receiver, and returns the locked state so the channel can discard its messages.
+    pub fn drop_receiver(&self) -> MutexGuard<'_, State<Q>> {
+        let mut state = self.lock();
+        state.receiver = false;
+        state
+    }
+
+    /// Takes a message from the queue with the pop function, or registers the receiver waker if the queue is empty.
+    /// Returns `None` once the queue is empty, and all the senders have been dropped.
+    pub fn poll_recv<T>(
+        &self,
+        cx: &mut Context<'_>,
+        pop: impl FnOnce(&mut Q) -> Option<T>,
+    ) -> Poll<Option<T>> {
+        let mut state = self.lock();
+
+        if let Some(value) = pop(&mut state.queue) {
+            return Poll::Ready(Some(value));
+        }
+
+        if state.senders == 0 {
+            return Poll::Ready(None);
+        }
+
+        state.recv_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Takes a message from the queue with the pop function, without waiting.
+    pub fn try_recv<T>(&self, pop: impl FnOnce(&mut Q) -> Option<T>) -> Result<T, TryRecvError> {
+        let mut state = self.lock();
+
+        match pop(&mut state.queue) {
+            Some(value) => Ok(value),
+            None if state.senders == 0 => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
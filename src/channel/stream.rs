@@ -0,0 +1,264 @@
+use crate::{error::SendError, Receiver, Sender};
+use futures_util::{
+    sink::Sink,
+    stream::{FusedStream, Stream},
+};
+use std::{
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+type RecvFuture<R, T> = Pin<Box<dyn Future<Output = (R, Option<T>)> + Send>>;
+type SendFuture<S, T> = Pin<Box<dyn Future<Output = (S, Result<(), SendError<T>>)> + Send>>;
+
+/// A [Stream](https://docs.rs/futures/0.3/futures/stream/trait.Stream.html) adapter for any lifeline [Receiver](./trait.Receiver.html).
+/// Created by [Receiver::into_stream](./trait.Receiver.html#method.into_stream).
+///
+/// The stream ends when the receiver returns `None`.
+///
+/// Some channels already implement `Stream` directly, and don't need the adapter.
+/// This includes the async-std and async-broadcast receivers, and flume receivers (via the inherent `into_stream` method).
+/// tokio mpsc receivers can be wrapped in an [MpscStream](./struct.MpscStream.html) or [UnboundedStream](./struct.UnboundedStream.html),
+/// which poll the receiver without allocating.  tokio broadcast and watch receivers use this adapter.
+///
+/// ## Example:
+/// ```
+/// use futures_util::StreamExt;
+/// use lifeline::prelude::*;
+/// use lifeline::{Receiver, Sender};
+/// use tokio::sync::mpsc;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// struct ExampleRecv(usize);
+/// impl Message<ExampleBus> for ExampleRecv {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct ExampleSend(usize);
+/// impl Message<ExampleBus> for ExampleSend {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     let tx = bus.tx::<ExampleRecv>()?;
+///     let rx = bus.rx::<ExampleRecv>()?;
+///     let tx_send = bus.tx::<ExampleSend>()?;
+///     let rx_send = bus.rx::<ExampleSend>()?;
+///     drop(bus);
+///
+///     tx.send(ExampleRecv(1)).await?;
+///     tx.send(ExampleRecv(2)).await?;
+///     drop(tx);
+///
+///     // forward messages between bus channels, and close the sink when the stream ends
+///     rx.into_stream()
+///         .map(|ExampleRecv(n)| Ok(ExampleSend(n * 10)))
+///         .forward(tx_send.into_sink())
+///         .await?;
+///
+///     let received: Vec<ExampleSend> = rx_send.into_stream().collect().await;
+///     assert_eq!(vec![ExampleSend(10), ExampleSend(20)], received);
+///     Ok(())
+/// }
+/// ```
+pub struct ReceiverStream<R, T> {
+    state: RecvState<R, T>,
+}
+
+enum RecvState<R, T> {
+    Idle(R),
+    Receiving(RecvFuture<R, T>),
+    Closed,
+}
+
+impl<R, T> ReceiverStream<R, T>
+where
+    R: Receiver<T> + Send + 'static,
+    T: Send + 'static,
+{
+    /// Wraps the receiver
+    pub fn new(rx: R) -> Self {
+        Self {
+            state: RecvState::Idle(rx),
+        }
+    }
+}
+
+// the receiver is never pinned, it's moved into a boxed future while a value is being received
+impl<R, T> Unpin for ReceiverStream<R, T> {}
+
+impl<R, T> Stream for ReceiverStream<R, T>
+where
+    R: Receiver<T> + Send + 'static,
+    T: Send + 'static,
+{
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+
+        loop {
+            match std::mem::replace(&mut this.state, RecvState::Closed) {
+                RecvState::Idle(mut rx) => {
+                    this.state = RecvState::Receiving(Box::pin(async move {
+                        let value = rx.recv().await;
+                        (rx, value)
+                    }));
+                }
+                RecvState::Receiving(mut future) => {
+                    return match future.as_mut().poll(cx) {
+                        Poll::Ready((rx, Some(value))) => {
+                            this.state = RecvState::Idle(rx);
+                            Poll::Ready(Some(value))
+                        }
+                        Poll::Ready((_rx, None)) => Poll::Ready(None),
+                        Poll::Pending => {
+                            this.state = RecvState::Receiving(future);
+                            Poll::Pending
+                        }
+                    };
+                }
+                RecvState::Closed => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<R, T> FusedStream for ReceiverStream<R, T>
+where
+    R: Receiver<T> + Send + 'static,
+    T: Send + 'static,
+{
+    fn is_terminated(&self) -> bool {
+        matches!(self.state, RecvState::Closed)
+    }
+}
+
+impl<R, T> Debug for ReceiverStream<R, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self.state {
+            RecvState::Idle(_) => "Idle",
+            RecvState::Receiving(_) => "Receiving",
+            RecvState::Closed => "Closed",
+        };
+
+        f.debug_struct("ReceiverStream")
+            .field("state", &state)
+            .finish()
+    }
+}
+
+/// A [Sink](https://docs.rs/futures/0.3/futures/sink/trait.Sink.html) adapter for any lifeline [Sender](./trait.Sender.html).
+/// Created by [Sender::into_sink](./trait.Sender.html#method.into_sink).
+///
+/// One message is sent at a time.  `poll_ready` waits for the previous message to be accepted by the channel.
+/// If a send fails, the error is returned and the sink is closed.  Closing the sink drops the sender.
+pub struct SenderSink<S, T: Debug> {
+    state: SendState<S, T>,
+}
+
+enum SendState<S, T: Debug> {
+    Idle(S),
+    Sending(SendFuture<S, T>),
+    Closed,
+}
+
+impl<S, T> SenderSink<S, T>
+where
+    S: Sender<T> + Send + 'static,
+    T: Debug + Send + 'static,
+{
+    /// Wraps the sender
+    pub fn new(tx: S) -> Self {
+        Self {
+            state: SendState::Idle(tx),
+        }
+    }
+}
+
+// the sender is never pinned, it's moved into a boxed future while a value is being sent
+impl<S, T: Debug> Unpin for SenderSink<S, T> {}
+
+impl<S, T> Sink<T> for SenderSink<S, T>
+where
+    S: Sender<T> + Send + 'static,
+    T: Debug + Send + 'static,
+{
+    type Error = SendError<T>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+
+        match std::mem::replace(&mut this.state, SendState::Closed) {
+            SendState::Idle(mut tx) => {
+                this.state = SendState::Sending(Box::pin(async move {
+                    let result = tx.send(item).await;
+                    (tx, result)
+                }));
+
+                Ok(())
+            }
+            state => {
+                // poll_ready must return Ok before start_send is called
+                this.state = state;
+                Err(SendError::Return(item))
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+
+        match std::mem::replace(&mut this.state, SendState::Closed) {
+            SendState::Idle(tx) => {
+                this.state = SendState::Idle(tx);
+                Poll::Ready(Ok(()))
+            }
+            SendState::Sending(mut future) => match future.as_mut().poll(cx) {
+                Poll::Ready((tx, Ok(()))) => {
+                    this.state = SendState::Idle(tx);
+                    Poll::Ready(Ok(()))
+                }
+                Poll::Ready((_tx, Err(err))) => Poll::Ready(Err(err)),
+                Poll::Pending => {
+                    this.state = SendState::Sending(future);
+                    Poll::Pending
+                }
+            },
+            SendState::Closed => Poll::Ready(Err(SendError::Closed)),
+        }
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if let SendState::Closed = self.state {
+            return Poll::Ready(Ok(()));
+        }
+
+        let result = futures_util::ready!(self.as_mut().poll_flush(cx));
+        self.get_mut().state = SendState::Closed;
+        Poll::Ready(result)
+    }
+}
+
+impl<S, T: Debug> Debug for SenderSink<S, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self.state {
+            SendState::Idle(_) => "Idle",
+            SendState::Sending(_) => "Sending",
+            SendState::Closed => "Closed",
+        };
+
+        f.debug_struct("SenderSink").field("state", &state).finish()
+    }
+}
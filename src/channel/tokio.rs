@@ -1,9 +1,25 @@
 use super::Channel;
-use crate::error::SendError as LifelineSendError;
-use crate::{error::type_name, impl_channel_clone, impl_channel_take};
+use crate::error::{
+    SendError as LifelineSendError, TryRecvError as LifelineTryRecvError,
+    TrySendError as LifelineTrySendError,
+};
+use crate::{
+    backpressure::SingleConsumer, error::type_name, impl_channel_clone, impl_channel_take,
+    spawn::sleep, Recv,
+};
 use async_trait::async_trait;
+use futures_util::{
+    future::{select, Either},
+    pin_mut,
+    stream::{FusedStream, Stream},
+};
 use log::debug;
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
 use tokio::sync::{broadcast, mpsc, oneshot, watch};
 
 impl<T: Send + 'static> Channel for mpsc::Sender<T> {
@@ -17,8 +33,14 @@ impl<T: Send + 'static> Channel for mpsc::Sender<T> {
     fn default_capacity() -> usize {
         16
     }
+
+    fn is_closed(tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        tx.map(|tx| tx.is_closed()).unwrap_or(false)
+    }
 }
 
+impl<T: Send + 'static> SingleConsumer for mpsc::Sender<T> {}
+
 impl_channel_clone!(mpsc::Sender<T>);
 impl_channel_take!(mpsc::Receiver<T>);
 
@@ -32,6 +54,52 @@ where
             .await
             .map_err(|err| LifelineSendError::Return(err.0))
     }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        mpsc::Sender::try_send(self, value).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(value) => LifelineTrySendError::Full(value),
+            mpsc::error::TrySendError::Closed(value) => LifelineTrySendError::Closed(value),
+        })
+    }
+
+    async fn send_timeout(
+        &mut self,
+        value: T,
+        timeout: Duration,
+    ) -> Result<(), LifelineSendError<T>> {
+        // reserving capacity can be cancelled without losing the message
+        let reserve = mpsc::Sender::reserve(self);
+        let timer = sleep(timeout);
+        pin_mut!(reserve, timer);
+
+        match select(reserve, timer).await {
+            Either::Left((Ok(permit), _)) => {
+                permit.send(value);
+                Ok(())
+            }
+            Either::Left((Err(_), _)) => Err(LifelineSendError::Return(value)),
+            Either::Right(_) => Err(LifelineSendError::Timeout(value)),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> crate::SharedSender<T> for mpsc::Sender<T>
+where
+    T: Debug + Send,
+{
+    async fn send_shared(&self, value: T) -> Result<(), LifelineSendError<T>> {
+        mpsc::Sender::send(self, value)
+            .await
+            .map_err(|err| LifelineSendError::Return(err.0))
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        mpsc::Sender::try_send(self, value).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(value) => LifelineTrySendError::Full(value),
+            mpsc::error::TrySendError::Closed(value) => LifelineTrySendError::Closed(value),
+        })
+    }
 }
 
 #[async_trait]
@@ -42,6 +110,113 @@ where
     async fn recv(&mut self) -> Option<T> {
         mpsc::Receiver::recv(self).await
     }
+
+    async fn recv_many(&mut self, buffer: &mut Vec<T>, limit: usize) -> usize {
+        mpsc::Receiver::recv_many(self, buffer, limit).await
+    }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        mpsc::Receiver::try_recv(self).map_err(|err| match err {
+            mpsc::error::TryRecvError::Empty => LifelineTryRecvError::Empty,
+            mpsc::error::TryRecvError::Disconnected => LifelineTryRecvError::Closed,
+        })
+    }
+}
+
+/// Unbounded mpsc channels never apply backpressure to the sender.  The capacity configured with `bus.capacity` is ignored.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use tokio::sync::mpsc;
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// struct ExampleEvent {}
+/// impl Message<ExampleBus> for ExampleEvent {
+///     type Channel = mpsc::UnboundedSender<Self>;
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     let tx = bus.tx::<ExampleEvent>()?;
+///     let mut rx = bus.rx::<ExampleEvent>()?;
+///
+///     for _ in 0..1024 {
+///         tx.send(ExampleEvent {})?;
+///     }
+///
+///     assert!(rx.try_recv().is_ok());
+///     Ok(())
+/// }
+/// ```
+impl<T: Send + 'static> Channel for mpsc::UnboundedSender<T> {
+    type Tx = Self;
+    type Rx = mpsc::UnboundedReceiver<T>;
+
+    fn channel(_capacity: usize) -> (Self::Tx, Self::Rx) {
+        mpsc::unbounded_channel()
+    }
+
+    fn default_capacity() -> usize {
+        0
+    }
+
+    fn is_closed(tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        tx.map(|tx| tx.is_closed()).unwrap_or(false)
+    }
+}
+
+impl_channel_clone!(mpsc::UnboundedSender<T>);
+impl_channel_take!(mpsc::UnboundedReceiver<T>);
+
+#[async_trait]
+impl<T> crate::Sender<T> for mpsc::UnboundedSender<T>
+where
+    T: Debug + Send,
+{
+    async fn send(&mut self, value: T) -> Result<(), LifelineSendError<T>> {
+        mpsc::UnboundedSender::send(self, value).map_err(|err| LifelineSendError::Return(err.0))
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        mpsc::UnboundedSender::send(self, value).map_err(|err| LifelineTrySendError::Closed(err.0))
+    }
+}
+
+#[async_trait]
+impl<T> crate::SharedSender<T> for mpsc::UnboundedSender<T>
+where
+    T: Debug + Send,
+{
+    async fn send_shared(&self, value: T) -> Result<(), LifelineSendError<T>> {
+        mpsc::UnboundedSender::send(self, value).map_err(|err| LifelineSendError::Return(err.0))
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        mpsc::UnboundedSender::send(self, value).map_err(|err| LifelineTrySendError::Closed(err.0))
+    }
+}
+
+#[async_trait]
+impl<T> crate::Receiver<T> for mpsc::UnboundedReceiver<T>
+where
+    T: Debug + Send,
+{
+    async fn recv(&mut self) -> Option<T> {
+        mpsc::UnboundedReceiver::recv(self).await
+    }
+
+    async fn recv_many(&mut self, buffer: &mut Vec<T>, limit: usize) -> usize {
+        mpsc::UnboundedReceiver::recv_many(self, buffer, limit).await
+    }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        mpsc::UnboundedReceiver::try_recv(self).map_err(|err| match err {
+            mpsc::error::TryRecvError::Empty => LifelineTryRecvError::Empty,
+            mpsc::error::TryRecvError::Disconnected => LifelineTryRecvError::Closed,
+        })
+    }
 }
 
 impl<T: Send + Clone + 'static> Channel for broadcast::Sender<T> {
@@ -66,6 +241,10 @@ impl<T: Send + Clone + 'static> Channel for broadcast::Sender<T> {
         // tx should always be here, but just in case.. tx.map( ... )
         rx.take().or_else(|| tx.map(|tx| tx.subscribe()))
     }
+
+    fn rx_available(rx: Option<&Self::Rx>, tx: Option<&Self::Tx>) -> bool {
+        rx.is_some() || tx.is_some()
+    }
 }
 
 impl_channel_clone!(broadcast::Sender<T>);
@@ -83,6 +262,30 @@ where
             .map(|_| ())
             .map_err(|err| LifelineSendError::Return(err.0))
     }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        broadcast::Sender::send(self, value)
+            .map(|_| ())
+            .map_err(|err| LifelineTrySendError::Closed(err.0))
+    }
+}
+
+#[async_trait]
+impl<T> crate::SharedSender<T> for broadcast::Sender<T>
+where
+    T: Debug + Send,
+{
+    async fn send_shared(&self, value: T) -> Result<(), LifelineSendError<T>> {
+        broadcast::Sender::send(self, value)
+            .map(|_| ())
+            .map_err(|err| LifelineSendError::Return(err.0))
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        broadcast::Sender::send(self, value)
+            .map(|_| ())
+            .map_err(|err| LifelineTrySendError::Closed(err.0))
+    }
 }
 
 #[async_trait]
@@ -108,6 +311,32 @@ where
             }
         }
     }
+
+    async fn recv_lossy(&mut self) -> Recv<T> {
+        match broadcast::Receiver::recv(self).await {
+            Ok(t) => Recv::Value(t),
+            Err(broadcast::error::RecvError::Closed) => Recv::Closed,
+            Err(broadcast::error::RecvError::Lagged(n)) => Recv::Lagged(n),
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        loop {
+            match broadcast::Receiver::try_recv(self) {
+                Ok(t) => return Ok(t),
+                Err(broadcast::error::TryRecvError::Empty) => {
+                    return Err(LifelineTryRecvError::Empty)
+                }
+                Err(broadcast::error::TryRecvError::Closed) => {
+                    return Err(LifelineTryRecvError::Closed)
+                }
+                Err(broadcast::error::TryRecvError::Lagged(n)) => {
+                    debug!("LAGGED {} {}", n, type_name::<T>());
+                    continue;
+                }
+            }
+        }
+    }
 }
 
 impl<T: Send + 'static> Channel for oneshot::Sender<T> {
@@ -121,11 +350,97 @@ impl<T: Send + 'static> Channel for oneshot::Sender<T> {
     fn default_capacity() -> usize {
         1
     }
+
+    fn is_closed(tx: Option<&Self::Tx>, _rx: Option<&Self::Rx>) -> bool {
+        tx.map(|tx| tx.is_closed()).unwrap_or(false)
+    }
 }
 
 impl_channel_take!(oneshot::Sender<T>);
 impl_channel_take!(oneshot::Receiver<T>);
 
+/// Sends the value on the oneshot channel.  A second send returns `SendError::Return(value)`.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::{error::SendError, Receiver, Sender};
+/// use tokio::sync::oneshot;
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug, PartialEq)]
+/// struct ExampleShutdown {}
+/// impl Message<ExampleBus> for ExampleShutdown {
+///     type Channel = oneshot::Sender<Self>;
+/// }
+///
+/// // code which is generic over lifeline senders can signal a oneshot channel
+/// async fn shutdown(tx: &mut impl Sender<ExampleShutdown>) -> Result<(), SendError<ExampleShutdown>> {
+///     tx.send(ExampleShutdown {}).await
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     let mut tx = bus.tx::<ExampleShutdown>()?;
+///     let mut rx = bus.rx::<ExampleShutdown>()?;
+///
+///     assert_eq!(Ok(()), shutdown(&mut tx).await);
+///     assert_eq!(
+///         Err(SendError::Return(ExampleShutdown {})),
+///         shutdown(&mut tx).await
+///     );
+///
+///     assert_eq!(Some(ExampleShutdown {}), rx.recv().await);
+///     assert_eq!(None, rx.recv().await);
+///     Ok(())
+/// }
+/// ```
+#[async_trait]
+impl<T> crate::Sender<T> for oneshot::Sender<T>
+where
+    T: Debug + Send,
+{
+    async fn send(&mut self, value: T) -> Result<(), LifelineSendError<T>> {
+        // the oneshot sender is consumed by send.  it's replaced with a sender which is already closed,
+        // so a second send returns the value.
+        let (closed, _) = oneshot::channel();
+        let tx = std::mem::replace(self, closed);
+
+        tx.send(value).map_err(LifelineSendError::Return)
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        let (closed, _) = oneshot::channel();
+        let tx = std::mem::replace(self, closed);
+
+        tx.send(value).map_err(LifelineTrySendError::Closed)
+    }
+}
+
+/// Receives the value from the oneshot channel.  Once the value has been received, recv returns None.
+#[async_trait]
+impl<T> crate::Receiver<T> for oneshot::Receiver<T>
+where
+    T: Debug + Send,
+{
+    async fn recv(&mut self) -> Option<T> {
+        // the tokio receiver panics if it's polled after completion, so try_recv is checked first
+        match self.try_recv() {
+            Ok(value) => Some(value),
+            Err(oneshot::error::TryRecvError::Closed) => None,
+            Err(oneshot::error::TryRecvError::Empty) => self.await.ok(),
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        oneshot::Receiver::try_recv(self).map_err(|err| match err {
+            oneshot::error::TryRecvError::Empty => LifelineTryRecvError::Empty,
+            oneshot::error::TryRecvError::Closed => LifelineTryRecvError::Closed,
+        })
+    }
+}
+
 impl<T> Channel for watch::Sender<T>
 where
     T: Default + Clone + Send + Sync + 'static,
@@ -140,6 +455,14 @@ where
     fn default_capacity() -> usize {
         1
     }
+
+    fn is_closed(tx: Option<&Self::Tx>, rx: Option<&Self::Rx>) -> bool {
+        // if the sender has been taken, the receiver can tell us if it was dropped
+        let tx_closed = tx.map(|tx| tx.is_closed()).unwrap_or(false);
+        let rx_closed = rx.map(|rx| rx.has_changed().is_err()).unwrap_or(false);
+
+        tx_closed || rx_closed
+    }
 }
 
 impl_channel_take!(watch::Sender<T>);
@@ -153,6 +476,24 @@ where
     async fn send(&mut self, value: T) -> Result<(), LifelineSendError<T>> {
         watch::Sender::send(self, value).map_err(|_| LifelineSendError::Closed)
     }
+
+    fn try_send(&mut self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        watch::Sender::send(self, value).map_err(|err| LifelineTrySendError::Closed(err.0))
+    }
+}
+
+#[async_trait]
+impl<T> crate::SharedSender<T> for watch::Sender<T>
+where
+    T: Clone + Debug + Send + Sync,
+{
+    async fn send_shared(&self, value: T) -> Result<(), LifelineSendError<T>> {
+        watch::Sender::send(self, value).map_err(|_| LifelineSendError::Closed)
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), LifelineTrySendError<T>> {
+        watch::Sender::send(self, value).map_err(|err| LifelineTrySendError::Closed(err.0))
+    }
 }
 
 #[async_trait]
@@ -166,4 +507,407 @@ where
             Err(_) => None,
         }
     }
+
+    fn try_recv(&mut self) -> Result<T, LifelineTryRecvError> {
+        match self.has_changed() {
+            Ok(true) => Ok(self.borrow_and_update().clone()),
+            Ok(false) => Err(LifelineTryRecvError::Empty),
+            Err(_) => Err(LifelineTryRecvError::Closed),
+        }
+    }
+}
+
+/// A [Stream](https://docs.rs/futures/0.3/futures/stream/trait.Stream.html) of messages from a tokio `mpsc::Receiver`.
+///
+/// tokio receivers don't implement `Stream`.  This wrapper polls the receiver directly, so unlike [ReceiverStream](./struct.ReceiverStream.html),
+/// it doesn't allocate a future for each message.
+///
+/// ## Example:
+/// ```
+/// use futures_util::StreamExt;
+/// use lifeline::prelude::*;
+/// use lifeline::MpscStream;
+/// use tokio::sync::mpsc;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug, PartialEq)]
+/// struct ExampleMessage(usize);
+/// impl Message<ExampleBus> for ExampleMessage {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     let tx = bus.tx::<ExampleMessage>()?;
+///     let rx = MpscStream::new(bus.rx::<ExampleMessage>()?);
+///     drop(bus);
+///
+///     tx.send(ExampleMessage(1)).await?;
+///     tx.send(ExampleMessage(2)).await?;
+///     drop(tx);
+///
+///     let received: Vec<ExampleMessage> = rx.collect().await;
+///     assert_eq!(vec![ExampleMessage(1), ExampleMessage(2)], received);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MpscStream<T> {
+    rx: mpsc::Receiver<T>,
+    terminated: bool,
+}
+
+impl<T> MpscStream<T> {
+    /// Wraps the receiver
+    pub fn new(rx: mpsc::Receiver<T>) -> Self {
+        Self {
+            rx,
+            terminated: false,
+        }
+    }
+
+    /// Returns the wrapped receiver
+    pub fn into_inner(self) -> mpsc::Receiver<T> {
+        self.rx
+    }
+}
+
+impl<T> From<mpsc::Receiver<T>> for MpscStream<T> {
+    fn from(rx: mpsc::Receiver<T>) -> Self {
+        Self::new(rx)
+    }
+}
+
+impl<T> Stream for MpscStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        if self.terminated {
+            return Poll::Ready(None);
+        }
+
+        let next = futures_util::ready!(self.rx.poll_recv(cx));
+        self.terminated = next.is_none();
+        Poll::Ready(next)
+    }
+}
+
+impl<T> FusedStream for MpscStream<T> {
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+/// A [Stream](https://docs.rs/futures/0.3/futures/stream/trait.Stream.html) of messages from a tokio `mpsc::UnboundedReceiver`.
+///
+/// Like [MpscStream](./struct.MpscStream.html), this polls the receiver directly.
+#[derive(Debug)]
+pub struct UnboundedStream<T> {
+    rx: mpsc::UnboundedReceiver<T>,
+    terminated: bool,
+}
+
+impl<T> UnboundedStream<T> {
+    /// Wraps the receiver
+    pub fn new(rx: mpsc::UnboundedReceiver<T>) -> Self {
+        Self {
+            rx,
+            terminated: false,
+        }
+    }
+
+    /// Returns the wrapped receiver
+    pub fn into_inner(self) -> mpsc::UnboundedReceiver<T> {
+        self.rx
+    }
+}
+
+impl<T> From<mpsc::UnboundedReceiver<T>> for UnboundedStream<T> {
+    fn from(rx: mpsc::UnboundedReceiver<T>) -> Self {
+        Self::new(rx)
+    }
+}
+
+impl<T> Stream for UnboundedStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        if self.terminated {
+            return Poll::Ready(None);
+        }
+
+        let next = futures_util::ready!(self.rx.poll_recv(cx));
+        self.terminated = next.is_none();
+        Poll::Ready(next)
+    }
+}
+
+impl<T> FusedStream for UnboundedStream<T> {
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MpscStream, UnboundedStream};
+    use crate::{
+        error::{SendError, TakeChannelError, TryRecvError, TrySendError},
+        lifeline_bus, Bus, Message, Receiver, Sender, SharedSender,
+    };
+    use futures_util::{stream::FusedStream, StreamExt};
+    use tokio::sync::{broadcast, mpsc, oneshot};
+
+    lifeline_bus!(struct TokioBus);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Queued(usize);
+
+    impl Message<TokioBus> for Queued {
+        type Channel = mpsc::Sender<Self>;
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Broadcast(usize);
+
+    impl Message<TokioBus> for Broadcast {
+        type Channel = broadcast::Sender<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Shutdown;
+
+    impl Message<TokioBus> for Shutdown {
+        type Channel = oneshot::Sender<Self>;
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Unbounded(usize);
+
+    impl Message<TokioBus> for Unbounded {
+        type Channel = mpsc::UnboundedSender<Self>;
+    }
+
+    #[tokio::test]
+    async fn unbounded_ignores_capacity() {
+        let bus = TokioBus::default();
+        bus.capacity::<Unbounded>(1).unwrap();
+
+        let mut tx = bus.tx::<Unbounded>().unwrap();
+        let mut rx = bus.rx::<Unbounded>().unwrap();
+
+        for n in 0..16 {
+            Sender::try_send(&mut tx, Unbounded(n)).unwrap();
+        }
+
+        for n in 0..16 {
+            assert_eq!(Some(Unbounded(n)), Receiver::recv(&mut rx).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn unbounded_closed_when_rx_dropped() {
+        let bus = TokioBus::default();
+        let mut tx = bus.tx::<Unbounded>().unwrap();
+        drop(bus.rx::<Unbounded>().unwrap());
+
+        assert_eq!(
+            Err(SendError::Return(Unbounded(1))),
+            Sender::send(&mut tx, Unbounded(1)).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(Unbounded(2))),
+            Sender::try_send(&mut tx, Unbounded(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn unbounded_rx_closed_when_tx_dropped() {
+        let bus = TokioBus::default();
+        let tx = bus.tx::<Unbounded>().unwrap();
+        let mut rx = bus.rx::<Unbounded>().unwrap();
+
+        tx.send(Unbounded(1)).unwrap();
+        drop(tx);
+        drop(bus);
+
+        assert_eq!(Ok(Unbounded(1)), Receiver::try_recv(&mut rx));
+        assert_eq!(Err(TryRecvError::Closed), Receiver::try_recv(&mut rx));
+    }
+
+    #[test]
+    fn unbounded_rx_taken_twice() {
+        let bus = TokioBus::default();
+        let _rx = bus.rx::<Unbounded>().unwrap();
+
+        assert!(matches!(
+            bus.rx::<Unbounded>(),
+            Err(TakeChannelError::AlreadyTaken(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn oneshot_sends_once() {
+        let bus = TokioBus::default();
+        let mut tx = bus.tx::<Shutdown>().unwrap();
+        let mut rx = bus.rx::<Shutdown>().unwrap();
+
+        assert_eq!(Err(TryRecvError::Empty), Receiver::try_recv(&mut rx));
+
+        Sender::send(&mut tx, Shutdown).await.unwrap();
+        assert_eq!(
+            Err(SendError::Return(Shutdown)),
+            Sender::send(&mut tx, Shutdown).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(Shutdown)),
+            Sender::try_send(&mut tx, Shutdown)
+        );
+
+        // the tokio receiver panics if it's polled after completion, so this checks the value is only received once
+        assert_eq!(Some(Shutdown), Receiver::recv(&mut rx).await);
+        assert_eq!(None, Receiver::recv(&mut rx).await);
+        assert_eq!(Err(TryRecvError::Closed), Receiver::try_recv(&mut rx));
+    }
+
+    #[tokio::test]
+    async fn oneshot_closed_when_rx_dropped() {
+        let bus = TokioBus::default();
+        let mut tx = bus.tx::<Shutdown>().unwrap();
+        drop(bus.rx::<Shutdown>().unwrap());
+
+        assert!(matches!(
+            bus.rx::<Shutdown>(),
+            Err(TakeChannelError::AlreadyTaken(_))
+        ));
+        assert_eq!(
+            Err(SendError::Return(Shutdown)),
+            Sender::send(&mut tx, Shutdown).await
+        );
+    }
+
+    #[tokio::test]
+    async fn mpsc_stream_ends_when_closed() {
+        let bus = TokioBus::default();
+        let tx = bus.tx::<Queued>().unwrap();
+        let mut rx = MpscStream::new(bus.rx::<Queued>().unwrap());
+        drop(bus);
+
+        tx.send(Queued(1)).await.unwrap();
+        assert_eq!(Some(Queued(1)), rx.next().await);
+        assert!(!rx.is_terminated());
+
+        drop(tx);
+        assert_eq!(None, rx.next().await);
+        assert!(rx.is_terminated());
+        assert_eq!(None, rx.next().await);
+    }
+
+    #[tokio::test]
+    async fn unbounded_stream_collects() {
+        let bus = TokioBus::default();
+        let tx = bus.tx::<Unbounded>().unwrap();
+        let rx: UnboundedStream<_> = bus.rx::<Unbounded>().unwrap().into();
+        drop(bus);
+
+        for n in 0..3 {
+            tx.send(Unbounded(n)).unwrap();
+        }
+        drop(tx);
+
+        let received: Vec<Unbounded> = rx.collect().await;
+        assert_eq!(vec![Unbounded(0), Unbounded(1), Unbounded(2)], received);
+    }
+
+    #[tokio::test]
+    async fn broadcast_stream_skips_lagged() {
+        let bus = TokioBus::default();
+        bus.capacity::<Broadcast>(2).unwrap();
+
+        let tx = bus.tx::<Broadcast>().unwrap();
+        let rx = bus.rx::<Broadcast>().unwrap();
+        drop(bus);
+
+        for n in 0..4 {
+            tx.send(Broadcast(n)).unwrap();
+        }
+        drop(tx);
+
+        let received: Vec<Broadcast> = Receiver::into_stream(rx).collect().await;
+        assert_eq!(vec![Broadcast(2), Broadcast(3)], received);
+    }
+
+    #[tokio::test]
+    async fn mpsc_send_shared() {
+        let bus = TokioBus::default();
+        bus.capacity::<Queued>(2).unwrap();
+
+        let tx = bus.tx::<Queued>().unwrap();
+        let mut rx = bus.rx::<Queued>().unwrap();
+
+        let (first, second) = tokio::join!(tx.send_shared(Queued(1)), tx.send_shared(Queued(2)));
+        assert_eq!(Ok(()), first);
+        assert_eq!(Ok(()), second);
+        assert_eq!(
+            Err(TrySendError::Full(Queued(3))),
+            tx.try_send_shared(Queued(3))
+        );
+
+        assert_eq!(Some(Queued(1)), Receiver::recv(&mut rx).await);
+        assert_eq!(Ok(()), tx.try_send_shared(Queued(3)));
+    }
+
+    #[tokio::test]
+    async fn mpsc_send_shared_with_dropped_receiver() {
+        let bus = TokioBus::default();
+        let tx = bus.tx::<Queued>().unwrap();
+        drop(bus.rx::<Queued>().unwrap());
+        drop(bus);
+
+        assert_eq!(
+            Err(SendError::Return(Queued(1))),
+            tx.send_shared(Queued(1)).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(Queued(2))),
+            tx.try_send_shared(Queued(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn broadcast_send_shared() {
+        let bus = TokioBus::default();
+        let tx = bus.tx::<Broadcast>().unwrap();
+        let mut rx = bus.rx::<Broadcast>().unwrap();
+
+        tx.send_shared(Broadcast(1)).await.unwrap();
+        tx.try_send_shared(Broadcast(2)).unwrap();
+
+        assert_eq!(Some(Broadcast(1)), Receiver::recv(&mut rx).await);
+        assert_eq!(Some(Broadcast(2)), Receiver::recv(&mut rx).await);
+
+        drop(rx);
+        assert_eq!(
+            Err(TrySendError::Closed(Broadcast(3))),
+            tx.try_send_shared(Broadcast(3))
+        );
+    }
+
+    #[tokio::test]
+    async fn unbounded_send_shared() {
+        let bus = TokioBus::default();
+        let tx = bus.tx::<Unbounded>().unwrap();
+        let rx = bus.rx::<Unbounded>().unwrap();
+
+        tx.send_shared(Unbounded(1)).await.unwrap();
+        tx.try_send_shared(Unbounded(2)).unwrap();
+
+        drop(rx);
+        assert_eq!(
+            Err(SendError::Return(Unbounded(3))),
+            tx.send_shared(Unbounded(3)).await
+        );
+    }
 }
@@ -0,0 +1,496 @@
+//! Channel capacities loaded from external configuration, so queue sizes can be tuned without recompiling.
+//!
+//! A [CapacityConfig](./struct.CapacityConfig.html) maps message type names (without module paths, e.g. `ExampleMessage`) to capacities.
+//! It can be loaded from an environment variable, or from JSON and TOML with the `config-json` and `config-toml` features.
+//!
+//! The config is applied to the bus at construction, before channels are linked.  Each message type carried by the bus is listed,
+//! and names in the config which do not match a listed message are rejected:
+//! ```
+//! use lifeline::prelude::*;
+//! use lifeline::config::CapacityConfig;
+//! use tokio::sync::{broadcast, mpsc};
+//!
+//! lifeline_bus!(pub struct ExampleBus);
+//!
+//! #[derive(Debug)]
+//! struct ExampleRecv {}
+//! impl Message<ExampleBus> for ExampleRecv {
+//!     type Channel = mpsc::Sender<Self>;
+//! }
+//!
+//! #[derive(Debug, Clone)]
+//! struct ExampleSend {}
+//! impl Message<ExampleBus> for ExampleSend {
+//!     type Channel = broadcast::Sender<Self>;
+//! }
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let config = CapacityConfig::parse("ExampleRecv=1024, ExampleSend=64")?;
+//!
+//!     let bus = ExampleBus::default();
+//!     config
+//!         .apply(&bus)
+//!         .message::<ExampleRecv>()
+//!         .message::<ExampleSend>()
+//!         .finish()?;
+//!
+//!     let typo = CapacityConfig::parse("ExampleRecv=1024, ExampleSendd=64")?;
+//!     let bus = ExampleBus::default();
+//!     assert!(typo.apply(&bus).message::<ExampleRecv>().finish().is_err());
+//!
+//!     // ExampleRecv was not configured, so it can still be configured
+//!     assert!(bus.capacity::<ExampleRecv>(16).is_ok());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    error::{type_name, AlreadyLinkedError, CapacityConfigError, DuplicateMessageError},
+    Bus, Message,
+};
+use std::{
+    any::TypeId,
+    collections::{BTreeMap, BTreeSet},
+    env::{self, VarError},
+};
+
+/// A map of message type names to channel capacities.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapacityConfig {
+    capacities: BTreeMap<String, usize>,
+}
+
+impl CapacityConfig {
+    /// Constructs an empty config.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the capacity for the message type name.  Returns an error if the capacity is zero.
+    pub fn insert(
+        &mut self,
+        message: impl Into<String>,
+        capacity: usize,
+    ) -> Result<(), CapacityConfigError> {
+        let message = message.into();
+
+        if capacity == 0 {
+            return Err(CapacityConfigError::parse(format!(
+                "capacity for {} must be greater than zero",
+                message
+            )));
+        }
+
+        self.capacities.insert(message, capacity);
+        Ok(())
+    }
+
+    /// Returns the configured capacity for the message type name.
+    pub fn get(&self, message: &str) -> Option<usize> {
+        self.capacities.get(message).copied()
+    }
+
+    /// Returns true if the config has no capacities.
+    pub fn is_empty(&self) -> bool {
+        self.capacities.is_empty()
+    }
+
+    /// Parses a comma-separated list of `MessageName=capacity` pairs, such as `ExampleRecv=1024,ExampleSend=64`.
+    pub fn parse(source: &str) -> Result<Self, CapacityConfigError> {
+        let mut config = Self::new();
+
+        for pair in source.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let mut parts = pair.splitn(2, '=');
+            let message = parts.next().unwrap_or_default().trim();
+            let capacity = parts.next().map(str::trim).ok_or_else(|| {
+                CapacityConfigError::parse(format!("expected MessageName=capacity, found {}", pair))
+            })?;
+
+            let capacity = capacity.parse().map_err(|_| {
+                CapacityConfigError::parse(format!(
+                    "invalid capacity for {}: {}",
+                    message, capacity
+                ))
+            })?;
+
+            config.insert(message, capacity)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Loads the config from the environment variable, using the format described in [parse](#method.parse).
+    ///
+    /// If the variable is not set, returns an empty config.
+    pub fn from_env(key: &str) -> Result<Self, CapacityConfigError> {
+        match env::var(key) {
+            Ok(value) => Self::parse(value.as_str()),
+            Err(VarError::NotPresent) => Ok(Self::new()),
+            Err(VarError::NotUnicode(_)) => Err(CapacityConfigError::parse(format!(
+                "environment variable {} is not unicode",
+                key
+            ))),
+        }
+    }
+
+    /// Loads the config from a JSON object, such as `{ "ExampleRecv": 1024 }`.
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::config::CapacityConfig;
+    ///
+    /// let config = CapacityConfig::from_json(r#"{ "ExampleRecv": 1024 }"#).unwrap();
+    /// assert_eq!(Some(1024), config.get("ExampleRecv"));
+    /// ```
+    #[cfg(feature = "config-json")]
+    pub fn from_json(source: &str) -> Result<Self, CapacityConfigError> {
+        let map: BTreeMap<String, usize> = serde_json::from_str(source)
+            .map_err(|err| CapacityConfigError::parse(err.to_string()))?;
+
+        Self::from_map(map)
+    }
+
+    /// Loads the config from a TOML table, such as `ExampleRecv = 1024`.
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::config::CapacityConfig;
+    ///
+    /// let config = CapacityConfig::from_toml("ExampleRecv = 1024").unwrap();
+    /// assert_eq!(Some(1024), config.get("ExampleRecv"));
+    /// ```
+    #[cfg(feature = "config-toml")]
+    pub fn from_toml(source: &str) -> Result<Self, CapacityConfigError> {
+        let map: BTreeMap<String, usize> =
+            toml::from_str(source).map_err(|err| CapacityConfigError::parse(err.to_string()))?;
+
+        Self::from_map(map)
+    }
+
+    /// Begins applying the config to the bus.  Each message carried by the bus should be listed with [message](./struct.ApplyCapacity.html#method.message).
+    pub fn apply<'a, B: Bus>(&'a self, bus: &'a B) -> ApplyCapacity<'a, B> {
+        ApplyCapacity {
+            config: self,
+            bus,
+            pending: Vec::new(),
+            error: None,
+        }
+    }
+
+    #[cfg(any(feature = "config-json", feature = "config-toml"))]
+    fn from_map(map: BTreeMap<String, usize>) -> Result<Self, CapacityConfigError> {
+        let mut config = Self::new();
+        for (message, capacity) in map {
+            config.insert(message, capacity)?;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Applies a [CapacityConfig](./struct.CapacityConfig.html) to a bus.  Created by [CapacityConfig::apply](./struct.CapacityConfig.html#method.apply).
+///
+/// Listed messages are validated, and the capacities are applied to the bus by [finish](#method.finish).
+#[must_use = "the capacity config is validated and applied when finish is called"]
+#[derive(Debug)]
+pub struct ApplyCapacity<'a, B> {
+    config: &'a CapacityConfig,
+    bus: &'a B,
+    pending: Vec<PendingCapacity<'a, B>>,
+    error: Option<CapacityConfigError>,
+}
+
+/// A configured capacity, which is applied once the config has been validated
+#[derive(Debug)]
+struct PendingCapacity<'a, B> {
+    name: &'a str,
+    id: TypeId,
+    capacity: usize,
+    check: fn(&B) -> Result<(), AlreadyLinkedError>,
+    apply: fn(&B, usize) -> Result<(), AlreadyLinkedError>,
+}
+
+fn check_capacity<B, Msg>(bus: &B) -> Result<(), AlreadyLinkedError>
+where
+    B: Bus,
+    Msg: Message<B> + 'static,
+{
+    if bus.capacity_available::<Msg>() {
+        Ok(())
+    } else {
+        Err(AlreadyLinkedError::new::<B, Msg>())
+    }
+}
+
+fn apply_capacity<B, Msg>(bus: &B, capacity: usize) -> Result<(), AlreadyLinkedError>
+where
+    B: Bus,
+    Msg: Message<B> + 'static,
+{
+    bus.capacity::<Msg>(capacity)
+}
+
+impl<'a, B: Bus> ApplyCapacity<'a, B> {
+    /// Lists the message type.  If the config contains its type name, the capacity is applied by [finish](#method.finish).
+    ///
+    /// If another listed message type has the same type name, [finish](#method.finish) returns an error, as the configured capacity is ambiguous.
+    pub fn message<Msg>(mut self) -> Self
+    where
+        Msg: Message<B> + 'static,
+    {
+        if self.error.is_some() {
+            return self;
+        }
+
+        let name = type_name::<Msg>();
+        if let Some((name, capacity)) = self.config.capacities.get_key_value(name) {
+            let id = TypeId::of::<Msg>();
+            if let Some(pending) = self.pending.iter().find(|pending| pending.name == name) {
+                if pending.id != id {
+                    self.error = Some(CapacityConfigError::DuplicateMessage(
+                        DuplicateMessageError::new::<B, Msg>(),
+                    ));
+                }
+
+                return self;
+            }
+
+            self.pending.push(PendingCapacity {
+                name: name.as_str(),
+                id,
+                capacity: *capacity,
+                check: check_capacity::<B, Msg>,
+                apply: apply_capacity::<B, Msg>,
+            });
+        }
+
+        self
+    }
+
+    /// Validates the config, and applies the capacities to the bus.
+    ///
+    /// Returns an error if two listed messages have the same type name, if the config contains message names which were not listed,
+    /// or if a configured channel was already linked (or its capacity was already configured).  If validation fails, no capacities are applied.
+    pub fn finish(self) -> Result<(), CapacityConfigError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+
+        let listed: BTreeSet<&str> = self.pending.iter().map(|pending| pending.name).collect();
+        let unknown: Vec<String> = self
+            .config
+            .capacities
+            .keys()
+            .filter(|name| !listed.contains(name.as_str()))
+            .cloned()
+            .collect();
+
+        if !unknown.is_empty() {
+            return Err(CapacityConfigError::unknown_messages::<B>(unknown));
+        }
+
+        for pending in &self.pending {
+            (pending.check)(self.bus).map_err(CapacityConfigError::AlreadyLinked)?;
+        }
+
+        for pending in self.pending {
+            (pending.apply)(self.bus, pending.capacity)
+                .map_err(CapacityConfigError::AlreadyLinked)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "tokio-channels"))]
+mod tests {
+    use super::CapacityConfig;
+    use crate::{error::CapacityConfigError, lifeline_bus, Bus, Message};
+    use tokio::sync::mpsc;
+
+    lifeline_bus!(struct ConfigBus);
+
+    #[derive(Debug)]
+    struct First;
+
+    impl Message<ConfigBus> for First {
+        type Channel = mpsc::Sender<Self>;
+    }
+
+    #[derive(Debug)]
+    struct Second;
+
+    impl Message<ConfigBus> for Second {
+        type Channel = mpsc::Sender<Self>;
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            CapacityConfig::parse("First"),
+            Err(CapacityConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            CapacityConfig::parse("First=many"),
+            Err(CapacityConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            CapacityConfig::parse("First=0"),
+            Err(CapacityConfigError::Parse(_))
+        ));
+
+        let config = CapacityConfig::parse(" First = 4, ,Second=8 ").unwrap();
+        assert_eq!(Some(4), config.get("First"));
+        assert_eq!(Some(8), config.get("Second"));
+    }
+
+    #[test]
+    fn env_errors() {
+        std::env::set_var("LIFELINE_TEST_CAPACITY_INVALID", "First=-1");
+        assert!(matches!(
+            CapacityConfig::from_env("LIFELINE_TEST_CAPACITY_INVALID"),
+            Err(CapacityConfigError::Parse(_))
+        ));
+
+        let missing = CapacityConfig::from_env("LIFELINE_TEST_CAPACITY_MISSING").unwrap();
+        assert!(missing.is_empty());
+    }
+
+    #[cfg(feature = "config-json")]
+    #[test]
+    fn json_errors() {
+        assert!(matches!(
+            CapacityConfig::from_json(r#"{ "First": "many" }"#),
+            Err(CapacityConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            CapacityConfig::from_json(r#"{ "First": 0 }"#),
+            Err(CapacityConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            CapacityConfig::from_json("First=1"),
+            Err(CapacityConfigError::Parse(_))
+        ));
+    }
+
+    #[cfg(feature = "config-toml")]
+    #[test]
+    fn toml_errors() {
+        assert!(matches!(
+            CapacityConfig::from_toml("First = \"many\""),
+            Err(CapacityConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            CapacityConfig::from_toml("First = 0"),
+            Err(CapacityConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            CapacityConfig::from_toml("First = -1"),
+            Err(CapacityConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_names_are_rejected_before_applying() {
+        let config = CapacityConfig::parse("First=4, Secnd=8").unwrap();
+        let bus = ConfigBus::default();
+
+        let result = config
+            .apply(&bus)
+            .message::<First>()
+            .message::<Second>()
+            .finish();
+
+        match result {
+            Err(CapacityConfigError::UnknownMessages(err)) => {
+                assert_eq!(vec!["Secnd".to_string()], err.messages)
+            }
+            other => panic!("expected unknown messages, found {:?}", other),
+        }
+
+        // validation failed, so First was not configured
+        assert!(bus.capacity::<First>(1).is_ok());
+    }
+
+    #[test]
+    fn linked_channels_are_rejected_before_applying() {
+        let config = CapacityConfig::parse("First=4, Second=8").unwrap();
+        let bus = ConfigBus::default();
+        let _tx = bus.tx::<Second>().unwrap();
+
+        let result = config
+            .apply(&bus)
+            .message::<First>()
+            .message::<Second>()
+            .finish();
+
+        assert!(matches!(result, Err(CapacityConfigError::AlreadyLinked(_))));
+        assert!(bus.capacity::<First>(1).is_ok());
+    }
+
+    #[test]
+    fn configured_capacities_are_rejected_before_applying() {
+        let config = CapacityConfig::parse("First=4, Second=8").unwrap();
+        let bus = ConfigBus::default();
+        bus.capacity::<Second>(2).unwrap();
+
+        let result = config
+            .apply(&bus)
+            .message::<First>()
+            .message::<Second>()
+            .finish();
+
+        assert!(matches!(result, Err(CapacityConfigError::AlreadyLinked(_))));
+        assert!(bus.capacity::<First>(1).is_ok());
+    }
+
+    mod other {
+        use super::ConfigBus;
+        use crate::Message;
+        use tokio::sync::mpsc;
+
+        #[derive(Debug)]
+        pub struct First;
+
+        impl Message<ConfigBus> for First {
+            type Channel = mpsc::Sender<Self>;
+        }
+    }
+
+    #[test]
+    fn duplicate_names_are_rejected() {
+        let config = CapacityConfig::parse("First=4").unwrap();
+        let bus = ConfigBus::default();
+
+        let result = config
+            .apply(&bus)
+            .message::<First>()
+            .message::<other::First>()
+            .finish();
+
+        assert!(matches!(
+            result,
+            Err(CapacityConfigError::DuplicateMessage(_))
+        ));
+        assert!(bus.capacity::<First>(1).is_ok());
+    }
+
+    #[test]
+    fn capacities_are_applied() {
+        let config = CapacityConfig::parse("First=1").unwrap();
+        let bus = ConfigBus::default();
+
+        config
+            .apply(&bus)
+            .message::<First>()
+            .message::<First>()
+            .message::<Second>()
+            .finish()
+            .unwrap();
+
+        let tx = bus.tx::<First>().unwrap();
+        let _rx = bus.rx::<First>().unwrap();
+        tx.try_send(First).unwrap();
+        assert!(tx.try_send(First).is_err());
+    }
+}
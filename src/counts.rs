@@ -0,0 +1,85 @@
+use crate::error::type_name;
+use std::{
+    any::TypeId,
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Message counters, shared by the bus and the channel endpoints.  Used by the lag and drop trackers.
+///
+/// Counts are keyed by `TypeId`, so message types with the same name in different modules are counted separately.
+/// The type name is computed when the type is first counted, and is only used for display.
+#[derive(Clone, Default)]
+pub(crate) struct TypeCounts {
+    counts: Arc<Mutex<HashMap<TypeId, TypeCount>>>,
+}
+
+struct TypeCount {
+    name: &'static str,
+    count: u64,
+}
+
+impl TypeCounts {
+    /// Adds to the count for the type, and returns the type name
+    pub fn add<T: 'static>(&self, count: u64) -> &'static str {
+        let mut counts = self.counts.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let entry = counts
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| TypeCount {
+                name: type_name::<T>(),
+                count: 0,
+            });
+
+        entry.count += count;
+        entry.name
+    }
+
+    /// Returns the count for the type
+    pub fn get<T: 'static>(&self) -> u64 {
+        self.counts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&TypeId::of::<T>())
+            .map(|entry| entry.count)
+            .unwrap_or(0)
+    }
+
+    /// Returns the counts for each type name.  Types which share a name are summed.
+    pub fn by_name(&self) -> BTreeMap<String, u64> {
+        let counts = self.counts.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let mut by_name = BTreeMap::new();
+        for entry in counts.values() {
+            *by_name.entry(entry.name.to_string()).or_default() += entry.count;
+        }
+
+        by_name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeCounts;
+
+    mod first {
+        pub struct Sample;
+    }
+
+    mod second {
+        pub struct Sample;
+    }
+
+    #[test]
+    fn types_with_the_same_name_are_counted_separately() {
+        let counts = TypeCounts::default();
+
+        assert_eq!("Sample", counts.add::<first::Sample>(1));
+        counts.add::<second::Sample>(2);
+        counts.add::<second::Sample>(3);
+
+        assert_eq!(1, counts.get::<first::Sample>());
+        assert_eq!(5, counts.get::<second::Sample>());
+        assert_eq!(Some(&6), counts.by_name().get("Sample"));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{error::type_name, Bus, Channel, Message, Receiver};
+use log::warn;
+use std::fmt::Display;
+
+/// A shutdown report, which records the number of undelivered messages buffered on the bus, for each message type.
+///
+/// Messages are drained with [Bus::drain](./trait.Bus.html#method.drain), which only drains receivers still stored on the bus.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::DrainReport;
+/// use tokio::sync::{broadcast, mpsc};
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// struct ExampleRecv {}
+/// impl Message<ExampleBus> for ExampleRecv {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// #[derive(Debug, Clone)]
+/// struct ExampleSend {}
+/// impl Message<ExampleBus> for ExampleSend {
+///     type Channel = broadcast::Sender<Self>;
+/// }
+///
+/// fn main() {
+///     let bus = ExampleBus::default();
+///     let tx = bus.tx::<ExampleRecv>().unwrap();
+///     tx.try_send(ExampleRecv {}).unwrap();
+///
+///     let mut report = DrainReport::new();
+///     let undelivered: Vec<ExampleRecv> = report.drain(&bus);
+///     report.drain::<_, ExampleSend>(&bus);
+///
+///     assert_eq!(1, undelivered.len());
+///     assert_eq!(1, report.total());
+///     println!("undelivered messages: {}", report);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrainReport {
+    entries: Vec<DrainEntry>,
+}
+
+/// The number of undelivered messages for a message type, recorded in a [DrainReport](./struct.DrainReport.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrainEntry {
+    /// The message type name
+    pub message: String,
+    /// The number of messages which were buffered in the channel
+    pub count: usize,
+}
+
+impl DrainReport {
+    /// Constructs an empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drains the message channel on the bus, records the number of undelivered messages, and returns them.
+    pub fn drain<B, Msg>(&mut self, bus: &B) -> Vec<Msg>
+    where
+        B: Bus,
+        Msg: Message<B> + Send + 'static,
+        <Msg::Channel as Channel>::Rx: Receiver<Msg>,
+    {
+        let messages = bus.drain::<Msg>();
+
+        if !messages.is_empty() {
+            warn!(
+                "{} undelivered in {}: {} messages",
+                type_name::<Msg>(),
+                type_name::<B>(),
+                messages.len()
+            );
+        }
+
+        self.entries.push(DrainEntry {
+            message: type_name::<Msg>().to_string(),
+            count: messages.len(),
+        });
+
+        messages
+    }
+
+    /// Returns the recorded entries, in the order they were drained
+    pub fn entries(&self) -> &[DrainEntry] {
+        self.entries.as_slice()
+    }
+
+    /// Returns the total number of undelivered messages
+    pub fn total(&self) -> usize {
+        self.entries.iter().map(|entry| entry.count).sum()
+    }
+
+    /// Returns true if no undelivered messages were found
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+impl Display for DrainReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entries: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| format!("{}: {}", entry.message, entry.count))
+            .collect();
+
+        write!(f, "{}", entries.join(", "))
+    }
+}
+
+#[cfg(all(test, feature = "tokio-channels"))]
+mod tests {
+    use super::DrainReport;
+    use crate::{lifeline_bus, Bus, Message};
+    use tokio::sync::{broadcast, mpsc};
+
+    lifeline_bus!(struct DrainBus);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Queued(usize);
+
+    impl Message<DrainBus> for Queued {
+        type Channel = mpsc::Sender<Self>;
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Broadcast(usize);
+
+    impl Message<DrainBus> for Broadcast {
+        type Channel = broadcast::Sender<Self>;
+    }
+
+    const MESSAGES: usize = 500;
+
+    #[tokio::test]
+    async fn drains_more_than_the_cooperative_budget() {
+        let bus = DrainBus::default();
+        bus.capacity::<Queued>(MESSAGES).unwrap();
+
+        let tx = bus.tx::<Queued>().unwrap();
+        for n in 0..MESSAGES {
+            tx.send(Queued(n)).await.unwrap();
+        }
+
+        let drained = bus.drain::<Queued>();
+        assert_eq!((0..MESSAGES).map(Queued).collect::<Vec<_>>(), drained);
+    }
+
+    #[tokio::test]
+    async fn drains_large_broadcast() {
+        let bus = DrainBus::default();
+        bus.capacity::<Broadcast>(MESSAGES).unwrap();
+
+        let tx = bus.tx::<Broadcast>().unwrap();
+        for n in 0..MESSAGES {
+            tx.send(Broadcast(n)).unwrap();
+        }
+
+        let mut report = DrainReport::new();
+        assert_eq!(MESSAGES, report.drain::<_, Broadcast>(&bus).len());
+        assert_eq!(MESSAGES, report.total());
+    }
+
+    #[test]
+    fn taken_receivers_are_not_drained() {
+        let bus = DrainBus::default();
+        let tx = bus.tx::<Queued>().unwrap();
+        let _rx = bus.rx::<Queued>().unwrap();
+        tx.try_send(Queued(1)).unwrap();
+
+        let mut report = DrainReport::new();
+        assert!(report.drain::<_, Queued>(&bus).is_empty());
+        assert!(report.is_empty());
+    }
+}
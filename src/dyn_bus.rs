@@ -1,14 +1,23 @@
 //! The DynBus implementation used by `lifeline_bus!`, and TypeId-based slot storage.
+mod key;
 mod macros;
 mod slot;
 mod storage;
 
 use crate::{
-    bus::{Message, Resource},
+    backpressure::BackpressurePolicy,
+    bus::{
+        __private::{MessageBinding, ResourceBinding},
+        Message, Resource,
+    },
     error::{AlreadyLinkedError, TakeChannelError, TakeResourceError},
+    intercept::Interceptor,
+    lag::LagPolicy,
     Bus, Channel,
 };
+use std::{collections::BTreeMap, time::Duration};
 
+pub use key::Key;
 pub use storage::DynBusStorage;
 
 /// An extension trait which defines operations on a DynBus, which stores `box dyn` trait objects internally.
@@ -51,8 +60,259 @@ pub trait DynBus: Bus {
 
     /// Returns the `DynBusStorage` struct which manages the trait object slots.
     fn storage(&self) -> &DynBusStorage<Self>;
+
+    /// Enables or disables auto-relink.  If enabled, channels which have been closed are relinked on the next `rx` or `tx` call.
+    ///
+    /// This allows a restarted service to take a fresh `mpsc::Receiver`, after the previous receiver was dropped.
+    /// Closed channels are detected using [Channel::is_closed](../trait.Channel.html#method.is_closed).
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::prelude::*;
+    /// use tokio::sync::mpsc;
+    /// lifeline_bus!(pub struct ExampleBus);
+    ///
+    /// #[derive(Debug)]
+    /// struct ExampleMessage {}
+    /// impl Message<ExampleBus> for ExampleMessage {
+    ///     type Channel = mpsc::Sender<Self>;
+    /// }
+    ///
+    /// fn main() {
+    ///     let bus = ExampleBus::default();
+    ///     bus.auto_relink(true);
+    ///
+    ///     let rx = bus.rx::<ExampleMessage>();
+    ///     drop(rx);
+    ///
+    ///     assert!(bus.rx::<ExampleMessage>().is_ok());
+    /// }
+    /// ```
+    fn auto_relink(&self, enabled: bool) {
+        self.storage().auto_relink(enabled);
+    }
+
+    /// Adds an interceptor, which is run on each message sent through an intercepted channel on this bus.
+    ///
+    /// Interceptors can observe messages (for logging, metrics, or auditing), drop them, or delay them (for fault injection in tests).
+    /// Messages are intercepted if their channel type is wrapped in [InterceptSender](../intercept/struct.InterceptSender.html).
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::prelude::*;
+    /// use lifeline::intercept::{Action, InterceptSender};
+    /// use tokio::sync::mpsc;
+    /// lifeline_bus!(pub struct ExampleBus);
+    ///
+    /// #[derive(Debug)]
+    /// struct ExampleMessage(usize);
+    /// impl Message<ExampleBus> for ExampleMessage {
+    ///     type Channel = InterceptSender<mpsc::Sender<Self>>;
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     use lifeline::Sender;
+    ///
+    ///     let bus = ExampleBus::default();
+    ///     bus.add_interceptor(|_type_name: &str, message: &dyn std::fmt::Debug| {
+    ///         match format!("{:?}", message).as_str() {
+    ///             "ExampleMessage(2)" => Action::Drop,
+    ///             _ => Action::Pass,
+    ///         }
+    ///     });
+    ///
+    ///     let mut tx = bus.tx::<ExampleMessage>()?;
+    ///     let mut rx = bus.rx::<ExampleMessage>()?;
+    ///
+    ///     tx.send(ExampleMessage(1)).await?;
+    ///     tx.send(ExampleMessage(2)).await?;
+    ///     drop(tx);
+    ///     bus.relink::<ExampleMessage>();
+    ///
+    ///     assert_eq!(1, rx.recv().await.unwrap().0);
+    ///     assert!(rx.recv().await.is_none());
+    ///     Ok(())
+    /// }
+    /// ```
+    fn add_interceptor(&self, interceptor: impl Interceptor) {
+        self.storage().add_interceptor(interceptor);
+    }
+
+    /// Sets a default timeout for sends on the bus, or removes it if `None`.
+    ///
+    /// The timeout applies to senders wrapped in [InterceptSender](../intercept/struct.InterceptSender.html), and to [PrioritySender](../struct.PrioritySender.html).
+    /// If a send waits longer than the timeout for channel capacity, it returns `SendError::Timeout(message)`,
+    /// and a warning is logged with the message type and the name of the sending task.
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::prelude::*;
+    /// use lifeline::error::SendError;
+    /// use lifeline::intercept::InterceptSender;
+    /// use std::time::Duration;
+    /// use tokio::sync::mpsc;
+    /// lifeline_bus!(pub struct ExampleBus);
+    ///
+    /// #[derive(Debug)]
+    /// struct ExampleMessage(usize);
+    /// impl Message<ExampleBus> for ExampleMessage {
+    ///     type Channel = InterceptSender<mpsc::Sender<Self>>;
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     use lifeline::Sender;
+    ///
+    ///     let bus = ExampleBus::default();
+    ///     bus.capacity::<ExampleMessage>(1)?;
+    ///     bus.set_send_timeout(Some(Duration::from_millis(10)));
+    ///
+    ///     let mut tx = bus.tx::<ExampleMessage>()?;
+    ///     let _rx = bus.rx::<ExampleMessage>()?;
+    ///
+    ///     tx.send(ExampleMessage(1)).await?;
+    ///     let blocked = tx.send(ExampleMessage(2)).await;
+    ///     assert!(matches!(blocked, Err(SendError::Timeout(ExampleMessage(2)))));
+    ///     Ok(())
+    /// }
+    /// ```
+    fn set_send_timeout(&self, timeout: Option<Duration>) {
+        self.storage().set_send_timeout(timeout);
+    }
+
+    /// Sets the lag policy, which is applied when a lag-tracked receiver falls behind and loses messages.
+    ///
+    /// Lag is tracked if the channel type is wrapped in [TrackLag](../lag/struct.TrackLag.html).  See the [lag](../lag/index.html) module for an example.
+    fn set_lag_policy(&self, policy: LagPolicy) {
+        self.storage().set_lag_policy(policy);
+    }
+
+    /// Sets the snapshot message for the type, which lagged receivers return when the lag policy is `LagPolicy::Resync`.
+    ///
+    /// The function is called each time a receiver is resynced, and should construct a message which carries the current state.
+    fn lag_snapshot<Msg>(&self, snapshot: impl Fn() -> Msg + Send + Sync + 'static)
+    where
+        Msg: Message<Self> + 'static,
+    {
+        self.storage().lag_snapshot(snapshot);
+    }
+
+    /// Returns the number of messages which have been lost by lag-tracked receivers of the message type.
+    ///
+    /// Lag tracking is opt-in: only receivers of channels wrapped in [TrackLag](../lag/struct.TrackLag.html) are counted.
+    /// Other broadcast receivers skip lagged messages without recording them, so the count is always zero.
+    fn lag_count<Msg>(&self) -> u64
+    where
+        Msg: Message<Self> + 'static,
+    {
+        self.storage().lag().count::<Msg>()
+    }
+
+    /// Returns the number of lost messages, for each lag-tracked message type name which has lagged.
+    ///
+    /// Message types which don't wrap their channel in [TrackLag](../lag/struct.TrackLag.html) never appear in the map,
+    /// even if their receivers have lagged.
+    fn lag_counts(&self) -> BTreeMap<String, u64> {
+        self.storage().lag().counts()
+    }
+
+    /// Returns the number of messages of the type which have been dropped by the backpressure policy.
+    ///
+    /// Policies are configured with [Bus::backpressure](../trait.Bus.html#method.backpressure).
+    fn dropped_count<Msg>(&self) -> u64
+    where
+        Msg: Message<Self> + 'static,
+    {
+        self.storage().drops().count::<Msg>()
+    }
+
+    /// Returns the number of dropped messages, for each message type name which has dropped messages.
+    fn dropped_counts(&self) -> BTreeMap<String, u64> {
+        self.storage().drops().counts()
+    }
+
+    /// Takes (or clones) the [Receiver](../trait.Receiver.html) of a keyed channel.
+    ///
+    /// Keyed channels allow a bus to carry several independent channels of the same message type (for example, one per shard or peer).
+    /// Each key has its own channel, which is independent from the unkeyed channel returned by `bus.rx::<Msg>()`.
+    /// Endpoints are taken or cloned with the same semantics as [bus.rx](../trait.Bus.html#tymethod.rx).
+    ///
+    /// ## Example:
+    /// ```
+    /// use lifeline::prelude::*;
+    /// use tokio::sync::mpsc;
+    /// lifeline_bus!(pub struct ExampleBus);
+    ///
+    /// #[derive(Debug)]
+    /// struct ExampleMessage {}
+    /// impl Message<ExampleBus> for ExampleMessage {
+    ///     type Channel = mpsc::Sender<Self>;
+    /// }
+    ///
+    /// fn main() {
+    ///     let bus = ExampleBus::default();
+    ///     let shard_1 = bus.rx_keyed::<ExampleMessage, _>(1usize);
+    ///     let shard_2 = bus.rx_keyed::<ExampleMessage, _>(2usize);
+    ///     assert!(shard_1.is_ok());
+    ///     assert!(shard_2.is_ok());
+    ///
+    ///     let taken = bus.rx_keyed::<ExampleMessage, _>(1usize);
+    ///     assert!(taken.is_err());
+    /// }
+    /// ```
+    fn rx_keyed<Msg, K>(&self, key: K) -> Result<<Msg::Channel as Channel>::Rx, TakeChannelError>
+    where
+        Msg: Message<Self> + 'static,
+        K: Key,
+    {
+        self.storage().clone_rx_keyed::<Msg, Self, K>(key)
+    }
+
+    /// Takes (or clones) the [Sender](../trait.Sender.html) of a keyed channel.
+    ///
+    /// Endpoints are taken or cloned with the same semantics as [bus.tx](../trait.Bus.html#tymethod.tx).
+    fn tx_keyed<Msg, K>(&self, key: K) -> Result<<Msg::Channel as Channel>::Tx, TakeChannelError>
+    where
+        Msg: Message<Self> + 'static,
+        K: Key,
+    {
+        self.storage().clone_tx_keyed::<Msg, Self, K>(key)
+    }
+
+    /// Configures the capacity of a keyed channel, if the linked channel implementation takes a capacity during initialization.
+    ///
+    /// Returns an [AlreadyLinkedError](../error/struct.AlreadyLinkedError.html), if the capacity has already been configured.
+    fn capacity_keyed<Msg, K>(&self, key: K, capacity: usize) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<Self> + 'static,
+        K: Key,
+    {
+        self.storage().capacity_keyed::<Msg, K>(key, capacity)
+    }
+
+    /// Configures the backpressure policy of a keyed channel.  See [Bus::backpressure](../trait.Bus.html#method.backpressure).
+    ///
+    /// Drops on keyed channels are counted with the unkeyed channel, in [dropped_count](./trait.DynBus.html#method.dropped_count).
+    ///
+    /// Returns an [AlreadyLinkedError](../error/struct.AlreadyLinkedError.html), if the policy has already been configured, or if the channel has already been linked.
+    fn backpressure_keyed<Msg, K>(
+        &self,
+        key: K,
+        policy: BackpressurePolicy,
+    ) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<Self> + 'static,
+        K: Key,
+    {
+        self.storage().backpressure_keyed::<Msg, K>(key, policy)
+    }
 }
 
+// all message and resource types can be carried by a dynamic bus
+impl<Msg, B: DynBus> MessageBinding<B> for Msg {}
+impl<Res, B: DynBus> ResourceBinding<B> for Res {}
+
 impl<T> Bus for T
 where
     T: DynBus,
@@ -82,10 +342,59 @@ where
         self.storage().capacity::<Msg>(capacity)
     }
 
+    fn backpressure<Msg>(&self, policy: BackpressurePolicy) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<Self> + 'static,
+    {
+        self.storage().backpressure::<Msg>(policy)
+    }
+
     fn resource<Res>(&self) -> Result<Res, TakeResourceError>
     where
         Res: Resource<Self>,
     {
         self.storage().clone_resource::<Res>()
     }
+
+    fn relink<Msg>(&self)
+    where
+        Msg: Message<Self> + 'static,
+    {
+        self.storage().relink::<Msg, Self>()
+    }
+
+    fn is_linked<Msg>(&self) -> bool
+    where
+        Msg: Message<Self> + 'static,
+    {
+        self.storage().is_linked::<Msg>()
+    }
+
+    fn capacity_available<Msg>(&self) -> bool
+    where
+        Msg: Message<Self> + 'static,
+    {
+        self.storage().capacity_available::<Msg>()
+    }
+
+    fn rx_available<Msg>(&self) -> bool
+    where
+        Msg: Message<Self> + 'static,
+    {
+        self.storage().rx_available::<Msg>()
+    }
+
+    fn tx_available<Msg>(&self) -> bool
+    where
+        Msg: Message<Self> + 'static,
+    {
+        self.storage().tx_available::<Msg>()
+    }
+
+    fn resource_available<Res>(&self) -> bool
+    where
+        Res: Resource<Self>,
+    {
+        self.storage().resource_available::<Res>()
+    }
 }
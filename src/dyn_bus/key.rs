@@ -0,0 +1,168 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+/// Identifies a channel in the bus storage: the message TypeId, and an optional key.
+///
+/// Unkeyed channels (from `bus.rx::<Msg>()`) have no key.  Keyed channels (from `bus.rx_keyed::<Msg>(key)`) are independent of the unkeyed channel,
+/// and of each other.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ChannelId {
+    message: TypeId,
+    key: Option<BusKey>,
+}
+
+impl ChannelId {
+    pub fn of<Msg: 'static>() -> Self {
+        Self {
+            message: TypeId::of::<Msg>(),
+            key: None,
+        }
+    }
+
+    pub fn keyed<Msg: 'static, K: Key>(key: K) -> Self {
+        Self {
+            message: TypeId::of::<Msg>(),
+            key: Some(BusKey::new(key)),
+        }
+    }
+}
+
+/// A value which can be used to key a channel on the bus.  Implemented for all hashable values, such as strings or integers.
+///
+/// Keys are compared by type and value, so `1u32` and `1u64` identify different channels.  String keys are the exception:
+/// `"shard"` is stored as a `String`, so it identifies the same channel as `String::from("shard")`.
+pub trait Key: Hash + Eq + Debug + Send + Sync + 'static {}
+
+impl<K> Key for K where K: Hash + Eq + Debug + Send + Sync + 'static {}
+
+/// A type-erased key, which can be compared and hashed.
+#[derive(Clone)]
+pub(crate) struct BusKey {
+    value: Arc<dyn DynKey>,
+}
+
+impl BusKey {
+    pub fn new<K: Key>(key: K) -> Self {
+        // string slices and owned strings name the same channel
+        if let Some(key) = (&key as &dyn Any).downcast_ref::<&'static str>() {
+            return Self {
+                value: Arc::new(String::from(*key)),
+            };
+        }
+
+        Self {
+            value: Arc::new(key),
+        }
+    }
+}
+
+impl Debug for BusKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl PartialEq for BusKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.value.eq_key(other.value.as_ref())
+    }
+}
+
+impl Eq for BusKey {}
+
+impl Hash for BusKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.as_any().type_id().hash(state);
+        self.value.hash_key(state);
+    }
+}
+
+/// Object-safe equality and hashing, implemented for all keys.
+trait DynKey: Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    fn eq_key(&self, other: &dyn DynKey) -> bool;
+
+    fn hash_key(&self, state: &mut dyn Hasher);
+}
+
+impl<K: Key> DynKey for K {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_key(&self, other: &dyn DynKey) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<K>()
+            .map(|other| self == other)
+            .unwrap_or(false)
+    }
+
+    fn hash_key(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChannelId;
+    use std::{
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    };
+
+    struct MessageA;
+    struct MessageB;
+
+    fn hash(id: &ChannelId) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equal_keys_identify_one_channel() {
+        let a = ChannelId::keyed::<MessageA, _>("shard");
+        let b = ChannelId::keyed::<MessageA, _>("shard");
+
+        assert_eq!(a, b);
+        assert_eq!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    fn keys_are_compared_by_type_and_value() {
+        let str_key = ChannelId::keyed::<MessageA, _>("shard");
+
+        assert_ne!(str_key, ChannelId::keyed::<MessageA, _>("other"));
+        assert_ne!(
+            ChannelId::keyed::<MessageA, _>(1u32),
+            ChannelId::keyed::<MessageA, _>(1u64)
+        );
+    }
+
+    #[test]
+    fn string_keys_are_normalized() {
+        let str_key = ChannelId::keyed::<MessageA, _>("shard");
+        let string_key = ChannelId::keyed::<MessageA, _>(String::from("shard"));
+
+        assert_eq!(str_key, string_key);
+        assert_eq!(hash(&str_key), hash(&string_key));
+    }
+
+    #[test]
+    fn keyed_channels_are_independent_of_unkeyed() {
+        assert_ne!(
+            ChannelId::of::<MessageA>(),
+            ChannelId::keyed::<MessageA, _>(())
+        );
+        assert_ne!(
+            ChannelId::keyed::<MessageA, _>(1usize),
+            ChannelId::keyed::<MessageB, _>(1usize)
+        );
+    }
+}
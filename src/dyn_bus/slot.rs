@@ -3,15 +3,15 @@ use crate::{error::type_name, Channel, Storage};
 use std::{any::Any, fmt::Debug};
 
 pub(crate) struct BusSlot {
-    name: String,
+    name: &'static str,
     value: Option<Box<dyn Any + Send>>,
 }
 
 impl Debug for BusSlot {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let string = match self.value {
-            Some(_) => format!("BusSlot<{}>::Some(_)", self.name.as_str()),
-            None => format!("BusSlot<{}>::Empty", self.name.as_str()),
+            Some(_) => format!("BusSlot<{}>::Some(_)", self.name),
+            None => format!("BusSlot<{}>::Empty", self.name),
         };
 
         f.debug_struct(string.as_str()).finish()
@@ -34,6 +34,10 @@ impl BusSlot {
         }
     }
 
+    pub fn is_some(&self) -> bool {
+        self.value.is_some()
+    }
+
     pub fn put<T: Send + 'static>(&mut self, value: T) {
         self.value = Some(Box::new(value))
     }
@@ -48,6 +52,16 @@ impl BusSlot {
             .map(|boxed| boxed.downcast_ref().unwrap())
     }
 
+    pub fn get_rx<Chan>(&self) -> Option<&Chan::Rx>
+    where
+        Chan: Channel,
+        Chan::Rx: Any + 'static,
+    {
+        self.value
+            .as_ref()
+            .map(|boxed| boxed.downcast_ref().unwrap())
+    }
+
     pub fn clone_rx<Chan>(&mut self, tx: Option<&Chan::Tx>) -> Option<Chan::Rx>
     where
         Chan: Channel,
@@ -1,58 +1,89 @@
 use crate::{
+    backpressure::{BackpressurePolicy, DropTracker},
     bus::{Link, Message, Resource},
     error::{type_name, AlreadyLinkedError, TakeChannelError, TakeResourceError},
+    intercept::{Interceptor, Interceptors},
+    lag::{LagPolicy, LagTracker},
     Bus, Channel,
 };
 
-use super::slot::BusSlot;
+use super::{
+    key::{ChannelId, Key},
+    slot::BusSlot,
+};
 use log::debug;
 use std::{
     any::TypeId,
-    collections::{HashMap, HashSet},
+    collections::{hash_map::DefaultHasher, HashMap},
     fmt::Debug,
+    hash::{Hash, Hasher},
     marker::PhantomData,
-    sync::{RwLock, RwLockWriteGuard},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+    time::Duration,
 };
+
+/// The number of lock shards used for channels, and for resources.
+const SHARDS: usize = 16;
+
 /// Dynamic bus storage based on trait object slots, for Senders, Receivers, and Resources.
 ///
-/// Most values are stored as `HashMap<TypeId, BusSlot>`
+/// Channels are stored as `HashMap<ChannelId, ChannelEntry>`, where the id is the message TypeId and an optional key.
+/// Resources are stored as `HashMap<TypeId, BusSlot>`.
+///
+/// The maps are split into shards, each behind its own lock, so services which take unrelated channels do not contend.
+/// Each operation locks a single shard.  If a thread panics while holding a lock, the lock is recovered rather than poisoned.
 #[derive(Debug)]
 pub struct DynBusStorage<B> {
-    state: RwLock<DynBusState>,
+    channels: Vec<Mutex<HashMap<ChannelId, ChannelEntry>>>,
+    resources: Vec<Mutex<HashMap<TypeId, BusSlot>>>,
+    auto_relink: AtomicBool,
+    interceptors: Interceptors,
+    lag: LagTracker,
+    drops: DropTracker,
     _bus: PhantomData<B>,
 }
 
-/// The internal state for:
-/// - channels, a set of message TypeIds for which the channel has been linked
-/// - capacity, the map from messageTypeId to the overriden channel capacity
-/// - tx, the map from message TypeId to the channel sender
-/// - rx, the map from message TypeId to the channel receiver
-/// - resource, the map from resource TypeId to the resource value
-#[derive(Debug)]
-struct DynBusState {
-    pub(crate) channels: HashSet<TypeId>,
-    pub(crate) capacity: HashMap<TypeId, usize>,
-    pub(crate) tx: HashMap<TypeId, BusSlot>,
-    pub(crate) rx: HashMap<TypeId, BusSlot>,
-    pub(crate) resources: HashMap<TypeId, BusSlot>,
+/// The internal state for a channel:
+/// - linked, true if the channel has been linked
+/// - capacity, the overriden channel capacity
+/// - backpressure, the configured backpressure policy
+/// - tx, the slot for the channel sender
+/// - rx, the slot for the channel receiver
+/// - generation, incremented each time the entry is configured or linked
+///
+/// Channels are constructed without holding the shard lock.  The generation is used to detect entries which were changed in the meantime.
+#[derive(Debug, Default)]
+struct ChannelEntry {
+    linked: bool,
+    capacity: Option<usize>,
+    backpressure: Option<BackpressurePolicy>,
+    tx: Option<BusSlot>,
+    rx: Option<BusSlot>,
+    generation: u64,
 }
 
-impl Default for DynBusState {
-    fn default() -> Self {
-        DynBusState {
-            channels: HashSet::new(),
-            capacity: HashMap::new(),
-            tx: HashMap::new(),
-            rx: HashMap::new(),
-            resources: HashMap::new(),
-        }
+impl ChannelEntry {
+    /// Stores the endpoints of a linked channel
+    fn set_linked(&mut self, tx: BusSlot, rx: BusSlot) {
+        self.linked = true;
+        self.tx = Some(tx);
+        self.rx = Some(rx);
+        self.generation += 1;
     }
 }
 
 impl<B: Bus> Default for DynBusStorage<B> {
     fn default() -> Self {
         DynBusStorage {
-            state: RwLock::new(DynBusState::default()),
+            channels: (0..SHARDS).map(|_| Mutex::new(HashMap::new())).collect(),
+            resources: (0..SHARDS).map(|_| Mutex::new(HashMap::new())).collect(),
+            auto_relink: AtomicBool::new(false),
+            interceptors: Interceptors::default(),
+            lag: LagTracker::default(),
+            drops: DropTracker::default(),
             _bus: PhantomData,
         }
     }
@@ -60,27 +91,77 @@ impl<B: Bus> Default for DynBusStorage<B> {
 
 impl<B: Bus> DynBusStorage<B> {
     /// Links a channel on the bus, locking the state and inserting BusSlots for the sender/receiver pair
+    ///
+    /// If auto-relink is enabled, and the linked channel has been closed, the channel is relinked.
     pub fn link_channel<Msg, Bus>(&self)
     where
         Msg: Message<B> + 'static,
     {
-        let id = TypeId::of::<Msg>();
+        drop(self.lock_linked::<Msg, Bus>(&ChannelId::of::<Msg>(), false));
+    }
+
+    /// Links a keyed channel on the bus.  Keyed channels are independent of the unkeyed channel for the message type.
+    pub fn link_channel_keyed<Msg, Bus, K>(&self, key: K)
+    where
+        Msg: Message<B> + 'static,
+        K: Key,
+    {
+        drop(self.lock_linked::<Msg, Bus>(&ChannelId::keyed::<Msg, K>(key), false));
+    }
+
+    /// Links a fresh channel on the bus, replacing the existing endpoints if the channel was already linked.
+    ///
+    /// The endpoints stored on the bus are dropped.  Endpoints which have already been taken remain connected to the old channel.
+    pub fn relink<Msg, Bus>(&self)
+    where
+        Msg: Message<B> + 'static,
+    {
+        drop(self.lock_linked::<Msg, Bus>(&ChannelId::of::<Msg>(), true));
+    }
 
-        if let Some(mut state) = self.try_lock(id) {
-            let capacity = state
-                .capacity
-                .get(&id)
-                .copied()
-                .unwrap_or(Msg::Channel::default_capacity());
+    /// Enables or disables auto-relink.  If enabled, channels which have been closed are relinked on the next `rx` or `tx` call.
+    ///
+    /// Closed channels are detected using [Channel::is_closed](../trait.Channel.html#method.is_closed).
+    pub fn auto_relink(&self, enabled: bool) {
+        self.auto_relink.store(enabled, Ordering::Relaxed);
+    }
 
-            let (tx, rx) = Msg::Channel::channel(capacity);
+    /// Adds an interceptor, which is run on each message sent through an intercepted channel.
+    ///
+    /// Interceptors apply to channels linked by the bus, and to senders stored with `store_channel`.
+    /// See [InterceptSender](../intercept/struct.InterceptSender.html) for details.
+    pub fn add_interceptor(&self, interceptor: impl Interceptor) {
+        self.interceptors.add(interceptor);
+    }
 
-            debug!("{} linked in {}", type_name::<Msg>(), type_name::<Bus>());
-            state.rx.insert(id, BusSlot::new(Some(rx)));
-            state.tx.insert(id, BusSlot::new(Some(tx)));
+    /// Sets the default send timeout for intercepted channels.
+    pub fn set_send_timeout(&self, timeout: Option<Duration>) {
+        self.interceptors.set_send_timeout(timeout);
+    }
 
-            state.channels.insert(id);
-        }
+    /// Returns the interceptors registered on the bus.
+    pub fn interceptors(&self) -> &Interceptors {
+        &self.interceptors
+    }
+
+    /// Sets the lag policy for lag-tracked receivers.
+    pub fn set_lag_policy(&self, policy: LagPolicy) {
+        self.lag.set_policy(policy);
+    }
+
+    /// Sets the snapshot message function for the type, which is used by `LagPolicy::Resync`.
+    pub fn lag_snapshot<Msg: 'static>(&self, snapshot: impl Fn() -> Msg + Send + Sync + 'static) {
+        self.lag.set_snapshot(snapshot);
+    }
+
+    /// Returns the lag tracker of the bus, which holds the lag policy and counters.
+    pub fn lag(&self) -> &LagTracker {
+        &self.lag
+    }
+
+    /// Returns the drop tracker of the bus, which counts messages dropped by backpressure policies.
+    pub fn drops(&self) -> &DropTracker {
+        &self.drops
     }
 
     /// Takes or clones the channel receiver, using the `Channel` trait implementation.
@@ -89,26 +170,20 @@ impl<B: Bus> DynBusStorage<B> {
     where
         Msg: Message<B> + 'static,
     {
-        self.link_channel::<Msg, Bus>();
-
-        let id = TypeId::of::<Msg>();
-
-        let mut state = self.state.write().unwrap();
-        let state = &mut *state;
-        let tx = &state.tx;
-        let rx = &mut state.rx;
-
-        let tx = tx
-            .get(&id)
-            .map(|slot| slot.get_tx::<Msg::Channel>())
-            .flatten();
-
-        let slot = rx
-            .get_mut(&id)
-            .ok_or_else(|| TakeChannelError::partial_take::<Bus, Msg>(Link::Rx))?;
+        self.clone_rx_id::<Msg, Bus>(ChannelId::of::<Msg>())
+    }
 
-        slot.clone_rx::<Msg::Channel>(tx)
-            .ok_or_else(|| TakeChannelError::already_taken::<Bus, Msg>(Link::Rx))
+    /// Takes or clones the receiver of a keyed channel, using the `Channel` trait implementation.
+    /// Returns an error if the endpoint cannot be taken.
+    pub fn clone_rx_keyed<Msg, Bus, K>(
+        &self,
+        key: K,
+    ) -> Result<<Msg::Channel as Channel>::Rx, TakeChannelError>
+    where
+        Msg: Message<B> + 'static,
+        K: Key,
+    {
+        self.clone_rx_id::<Msg, Bus>(ChannelId::keyed::<Msg, K>(key))
     }
 
     /// Takes or clones the channel sender, using the `Channel` trait implementation.
@@ -117,21 +192,20 @@ impl<B: Bus> DynBusStorage<B> {
     where
         Msg: Message<B> + 'static,
     {
-        self.link_channel::<Msg, Bus>();
-
-        let id = TypeId::of::<Msg>();
-
-        let mut state = self.state.write().unwrap();
-        let senders = &mut state.tx;
-
-        // if the channel is linked, but the slot is empty,
-        // this means the user used take_rx, but asked for tx
-        let slot = senders
-            .get_mut(&id)
-            .ok_or_else(|| TakeChannelError::partial_take::<Bus, Msg>(Link::Tx))?;
+        self.clone_tx_id::<Msg, Bus>(ChannelId::of::<Msg>())
+    }
 
-        slot.clone_tx::<Msg::Channel>()
-            .ok_or_else(|| TakeChannelError::already_taken::<Bus, Msg>(Link::Tx))
+    /// Takes or clones the sender of a keyed channel, using the `Channel` trait implementation.
+    /// Returns an error if the endpoint cannot be taken.
+    pub fn clone_tx_keyed<Msg, Bus, K>(
+        &self,
+        key: K,
+    ) -> Result<<Msg::Channel as Channel>::Tx, TakeChannelError>
+    where
+        Msg: Message<B> + 'static,
+        K: Key,
+    {
+        self.clone_tx_id::<Msg, Bus>(ChannelId::keyed::<Msg, K>(key))
     }
 
     /// Takes or clones the resource, using the `Storage` trait implementation.
@@ -142,8 +216,7 @@ impl<B: Bus> DynBusStorage<B> {
     {
         let id = TypeId::of::<Res>();
 
-        let mut state = self.state.write().unwrap();
-        let resources = &mut state.resources;
+        let mut resources = self.lock_resource(&id);
         let slot = resources
             .get_mut(&id)
             .ok_or_else(|| TakeResourceError::uninitialized::<Self, Res>())?;
@@ -156,16 +229,13 @@ impl<B: Bus> DynBusStorage<B> {
     pub fn store_resource<Res: Send + 'static, Bus>(&self, value: Res) {
         let id = TypeId::of::<Res>();
 
-        let mut state = self.state.write().unwrap();
-        let resources = &mut state.resources;
-
-        if !resources.contains_key(&id) {
-            resources.insert(id.clone(), BusSlot::empty::<Res>());
-        }
+        let mut resources = self.lock_resource(&id);
 
         debug!("{} stored in {}", type_name::<Res>(), type_name::<Bus>());
 
-        let slot = resources.get_mut(&id).unwrap();
+        let slot = resources
+            .entry(id)
+            .or_insert_with(|| BusSlot::empty::<Res>());
 
         slot.put(value);
     }
@@ -175,7 +245,7 @@ impl<B: Bus> DynBusStorage<B> {
     pub fn store_channel<Msg, Chan, Bus>(
         &self,
         rx: Option<Chan::Rx>,
-        tx: Option<Chan::Tx>,
+        mut tx: Option<Chan::Tx>,
     ) -> Result<(), AlreadyLinkedError>
     where
         Chan: Channel,
@@ -185,12 +255,7 @@ impl<B: Bus> DynBusStorage<B> {
             return Ok(());
         }
 
-        let id = TypeId::of::<Msg>();
-
-        let mut target = self.state.write().expect("cannot lock other");
-        if target.channels.contains(&id) {
-            return Err(AlreadyLinkedError::new::<Self, Msg>());
-        }
+        let id = ChannelId::of::<Msg>();
 
         let link = match (rx.is_some(), tx.is_some()) {
             (true, true) => Link::Both,
@@ -206,11 +271,30 @@ impl<B: Bus> DynBusStorage<B> {
             type_name::<Bus>(),
         );
 
-        target.channels.insert(id);
-        target.tx.insert(id.clone(), BusSlot::new(tx));
-        target.rx.insert(id.clone(), BusSlot::new(rx));
-
-        Ok(())
+        loop {
+            let (generation, policy) = {
+                let mut shard = self.lock_channel(&id);
+                let entry = shard.entry(id.clone()).or_default();
+                if entry.linked {
+                    return Err(AlreadyLinkedError::new::<Self, Msg>());
+                }
+
+                (entry.generation, entry.backpressure)
+            };
+
+            // the hooks run channel implementation code, so they are called without holding the lock
+            if let Some(ref mut tx) = tx {
+                Chan::intercept(tx, &self.interceptors);
+                Chan::backpressure(tx, policy.unwrap_or_default(), &self.drops);
+            }
+
+            let mut shard = self.lock_channel(&id);
+            let entry = shard.entry(id.clone()).or_default();
+            if entry.generation == generation {
+                entry.set_linked(BusSlot::new(tx), BusSlot::new(rx));
+                return Ok(());
+            }
+        }
     }
 
     /// Writes a capacity to the bus storage, for the given message type.
@@ -219,37 +303,659 @@ impl<B: Bus> DynBusStorage<B> {
     where
         Msg: Message<B> + 'static,
     {
-        let id = TypeId::of::<Msg>();
+        self.capacity_id::<Msg>(ChannelId::of::<Msg>(), capacity)
+    }
+
+    /// Writes a capacity to the bus storage, for the keyed channel.
+    /// Returns an error if the channel is already linked in the bus storage (as this capacity would do nothing).
+    pub fn capacity_keyed<Msg, K>(&self, key: K, capacity: usize) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<B> + 'static,
+        K: Key,
+    {
+        self.capacity_id::<Msg>(ChannelId::keyed::<Msg, K>(key), capacity)
+    }
+
+    /// Writes a backpressure policy to the bus storage, for the given message type.
+    /// Returns an error if the policy was already configured, or if the channel is already linked (as the policy would do nothing).
+    pub fn backpressure<Msg>(&self, policy: BackpressurePolicy) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<B> + 'static,
+    {
+        self.backpressure_id::<Msg>(ChannelId::of::<Msg>(), policy)
+    }
+
+    /// Writes a backpressure policy to the bus storage, for the keyed channel.
+    /// Returns an error if the policy was already configured, or if the channel is already linked (as the policy would do nothing).
+    pub fn backpressure_keyed<Msg, K>(
+        &self,
+        key: K,
+        policy: BackpressurePolicy,
+    ) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<B> + 'static,
+        K: Key,
+    {
+        self.backpressure_id::<Msg>(ChannelId::keyed::<Msg, K>(key), policy)
+    }
+
+    /// Returns true if the channel has been linked on the bus.  This does not link the channel.
+    pub fn is_linked<Msg>(&self) -> bool
+    where
+        Msg: Message<B> + 'static,
+    {
+        let id = ChannelId::of::<Msg>();
+        let shard = self.lock_channel(&id);
+
+        shard.get(&id).map(|entry| entry.linked).unwrap_or(false)
+    }
+
+    /// Returns true if the capacity could be configured, because the channel is not linked, and the capacity has not been configured.
+    pub fn capacity_available<Msg>(&self) -> bool
+    where
+        Msg: Message<B> + 'static,
+    {
+        let id = ChannelId::of::<Msg>();
+        let shard = self.lock_channel(&id);
+
+        shard
+            .get(&id)
+            .map(|entry| !entry.linked && entry.capacity.is_none())
+            .unwrap_or(true)
+    }
+
+    /// Returns true if the channel receiver could be taken (or cloned).  This does not link the channel, or take the receiver.
+    pub fn rx_available<Msg>(&self) -> bool
+    where
+        Msg: Message<B> + 'static,
+    {
+        let id = ChannelId::of::<Msg>();
+        let shard = self.lock_channel(&id);
+
+        match shard.get(&id) {
+            Some(entry) if entry.linked && !self.needs_relink::<Msg>(entry) => {
+                let tx = entry
+                    .tx
+                    .as_ref()
+                    .and_then(|slot| slot.get_tx::<Msg::Channel>());
+
+                let rx = entry
+                    .rx
+                    .as_ref()
+                    .and_then(|slot| slot.get_rx::<Msg::Channel>());
+
+                Msg::Channel::rx_available(rx, tx)
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns true if the channel sender could be taken (or cloned).  This does not link the channel, or take the sender.
+    pub fn tx_available<Msg>(&self) -> bool
+    where
+        Msg: Message<B> + 'static,
+    {
+        let id = ChannelId::of::<Msg>();
+        let shard = self.lock_channel(&id);
+
+        match shard.get(&id) {
+            Some(entry) if entry.linked && !self.needs_relink::<Msg>(entry) => entry
+                .tx
+                .as_ref()
+                .map(|slot| slot.is_some())
+                .unwrap_or(false),
+            _ => true,
+        }
+    }
+
+    /// Returns true if the resource has been stored, and has not been taken.  This does not take the resource.
+    pub fn resource_available<Res>(&self) -> bool
+    where
+        Res: Resource<B> + 'static,
+    {
+        let id = TypeId::of::<Res>();
+        let resources = self.lock_resource(&id);
+
+        resources
+            .get(&id)
+            .map(|slot| slot.is_some())
+            .unwrap_or(false)
+    }
+
+    fn clone_rx_id<Msg, Bus>(
+        &self,
+        id: ChannelId,
+    ) -> Result<<Msg::Channel as Channel>::Rx, TakeChannelError>
+    where
+        Msg: Message<B> + 'static,
+    {
+        let mut rx = {
+            let mut shard = self.lock_linked::<Msg, Bus>(&id, false);
+            let entry = shard.entry(id).or_default();
+
+            let tx = entry
+                .tx
+                .as_ref()
+                .and_then(|slot| slot.get_tx::<Msg::Channel>());
+
+            let slot = entry
+                .rx
+                .as_mut()
+                .ok_or_else(|| TakeChannelError::partial_take::<Bus, Msg>(Link::Rx))?;
+
+            slot.clone_rx::<Msg::Channel>(tx)
+                .ok_or_else(|| TakeChannelError::already_taken::<Bus, Msg>(Link::Rx))?
+        };
+
+        Msg::Channel::track_lag(&mut rx, &self.lag);
+        Ok(rx)
+    }
+
+    fn clone_tx_id<Msg, Bus>(
+        &self,
+        id: ChannelId,
+    ) -> Result<<Msg::Channel as Channel>::Tx, TakeChannelError>
+    where
+        Msg: Message<B> + 'static,
+    {
+        let mut shard = self.lock_linked::<Msg, Bus>(&id, false);
+        let entry = shard.entry(id).or_default();
+
+        // if the channel is linked, but the slot is empty,
+        // this means the user used take_rx, but asked for tx
+        let slot = entry
+            .tx
+            .as_mut()
+            .ok_or_else(|| TakeChannelError::partial_take::<Bus, Msg>(Link::Tx))?;
 
-        let state = self.state.read().unwrap();
+        slot.clone_tx::<Msg::Channel>()
+            .ok_or_else(|| TakeChannelError::already_taken::<Bus, Msg>(Link::Tx))
+    }
 
-        if state.capacity.contains_key(&id) {
+    fn capacity_id<Msg>(&self, id: ChannelId, capacity: usize) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<B> + 'static,
+    {
+        let mut shard = self.lock_channel(&id);
+        let entry = shard.entry(id).or_default();
+
+        if entry.linked || entry.capacity.is_some() {
             return Err(AlreadyLinkedError::new::<B, Msg>());
         }
 
-        drop(state);
+        entry.capacity = Some(capacity);
+        entry.generation += 1;
+
+        Ok(())
+    }
+
+    fn backpressure_id<Msg>(
+        &self,
+        id: ChannelId,
+        policy: BackpressurePolicy,
+    ) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<B> + 'static,
+    {
+        let mut shard = self.lock_channel(&id);
+        let entry = shard.entry(id).or_default();
 
-        let mut state = self.state.write().unwrap();
+        if entry.linked || entry.backpressure.is_some() {
+            return Err(AlreadyLinkedError::new::<B, Msg>());
+        }
 
-        state.capacity.insert(id, capacity);
+        entry.backpressure = Some(policy);
+        entry.generation += 1;
 
         Ok(())
     }
 
-    /// Attempts to lock the bus, and acquire the state for the given message TypeId.
-    fn try_lock(&self, id: TypeId) -> Option<RwLockWriteGuard<DynBusState>> {
-        let state = self.state.read().unwrap();
-        if state.channels.contains(&id) {
-            return None;
+    /// Locks the shard which contains the channel, and links the channel if it has not been linked (or if it needs to be relinked).
+    /// If `relink` is true, a fresh channel is always linked.
+    ///
+    /// The channel is constructed without holding the lock, as this runs channel implementation code.
+    /// If the entry was changed by another thread in the meantime, the new channel is discarded, and the entry is checked again.
+    fn lock_linked<Msg, Bus>(
+        &self,
+        id: &ChannelId,
+        relink: bool,
+    ) -> MutexGuard<'_, HashMap<ChannelId, ChannelEntry>>
+    where
+        Msg: Message<B> + 'static,
+    {
+        loop {
+            let (generation, linked, capacity, policy) = {
+                let mut shard = self.lock_channel(id);
+                let entry = shard.entry(id.clone()).or_default();
+                if !relink && entry.linked && !self.needs_relink::<Msg>(entry) {
+                    return shard;
+                }
+
+                (
+                    entry.generation,
+                    entry.linked,
+                    entry.capacity,
+                    entry.backpressure,
+                )
+            };
+
+            let (tx, rx) = self.build::<Msg>(capacity, policy);
+
+            let mut shard = self.lock_channel(id);
+            let entry = shard.entry(id.clone()).or_default();
+            if entry.generation == generation {
+                entry.set_linked(BusSlot::new(Some(tx)), BusSlot::new(Some(rx)));
+
+                let action = if linked { "relinked" } else { "linked" };
+                debug!(
+                    "{} {} in {}",
+                    type_name::<Msg>(),
+                    action,
+                    type_name::<Bus>()
+                );
+                return shard;
+            }
+        }
+    }
+
+    /// Constructs a new channel, and attaches the bus interceptors and backpressure policy.
+    fn build<Msg>(
+        &self,
+        capacity: Option<usize>,
+        policy: Option<BackpressurePolicy>,
+    ) -> (<Msg::Channel as Channel>::Tx, <Msg::Channel as Channel>::Rx)
+    where
+        Msg: Message<B> + 'static,
+    {
+        let capacity = capacity.unwrap_or_else(Msg::Channel::default_capacity);
+
+        let (mut tx, rx) = Msg::Channel::channel(capacity);
+        Msg::Channel::intercept(&mut tx, &self.interceptors);
+        Msg::Channel::backpressure(&mut tx, policy.unwrap_or_default(), &self.drops);
+
+        (tx, rx)
+    }
+
+    /// Returns true if auto-relink is enabled, and the channel has been closed.
+    fn needs_relink<Msg>(&self, entry: &ChannelEntry) -> bool
+    where
+        Msg: Message<B> + 'static,
+    {
+        if !self.auto_relink.load(Ordering::Relaxed) {
+            return false;
+        }
+
+        let tx = entry
+            .tx
+            .as_ref()
+            .and_then(|slot| slot.get_tx::<Msg::Channel>());
+
+        let rx = entry
+            .rx
+            .as_ref()
+            .and_then(|slot| slot.get_rx::<Msg::Channel>());
+
+        Msg::Channel::is_closed(tx, rx)
+    }
+
+    /// Locks the channel shard which contains the given channel id.
+    fn lock_channel(&self, id: &ChannelId) -> MutexGuard<'_, HashMap<ChannelId, ChannelEntry>> {
+        Self::lock(&self.channels[Self::shard(id)])
+    }
+
+    /// Locks the resource shard which contains the given resource TypeId.
+    fn lock_resource(&self, id: &TypeId) -> MutexGuard<'_, HashMap<TypeId, BusSlot>> {
+        Self::lock(&self.resources[Self::shard(id)])
+    }
+
+    fn shard<T: Hash>(id: &T) -> usize {
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        (hasher.finish() as usize) % SHARDS
+    }
+
+    /// Locks the mutex, recovering it if it was poisoned.
+    ///
+    /// Channels are constructed without holding the lock, and entries are only updated after channel code has returned.
+    /// Endpoints are cloned while the lock is held, so if that panics, the endpoint may be lost, but the entry is still valid.
+    fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+        mutex.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(all(test, feature = "tokio-channels"))]
+mod tests {
+    use super::{ChannelId, DynBusStorage};
+    use crate::{dyn_bus::DynBus, lifeline_bus, Bus, Channel, Message};
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Barrier,
+        },
+        thread,
+    };
+    use tokio::sync::{broadcast, mpsc};
+
+    lifeline_bus!(struct StressBus);
+
+    #[derive(Debug)]
+    struct MpscMessage(usize);
+
+    impl Message<StressBus> for MpscMessage {
+        type Channel = mpsc::Sender<Self>;
+    }
+
+    #[derive(Debug, Clone)]
+    struct BroadcastMessage;
+
+    impl Message<StressBus> for BroadcastMessage {
+        type Channel = broadcast::Sender<Self>;
+    }
+
+    /// A channel whose constructor panics the first time it's called
+    struct Fragile;
+
+    static FRAGILE_PANIC: AtomicBool = AtomicBool::new(true);
+
+    impl Channel for Fragile {
+        type Tx = mpsc::Sender<FragileMessage>;
+        type Rx = mpsc::Receiver<FragileMessage>;
+
+        fn channel(capacity: usize) -> (Self::Tx, Self::Rx) {
+            if FRAGILE_PANIC.swap(false, Ordering::SeqCst) {
+                panic!("channel construction failed");
+            }
+
+            mpsc::channel(capacity)
+        }
+
+        fn default_capacity() -> usize {
+            16
+        }
+    }
+
+    #[derive(Debug)]
+    struct FragileMessage;
+
+    impl Message<StressBus> for FragileMessage {
+        type Channel = Fragile;
+    }
+
+    const THREADS: usize = 32;
+
+    /// Runs the closure on many threads at once, and collects the results
+    fn concurrently<T, F>(bus: &Arc<StressBus>, f: F) -> Vec<T>
+    where
+        T: Send + 'static,
+        F: Fn(&StressBus, usize) -> T + Send + Sync + 'static,
+    {
+        let barrier = Arc::new(Barrier::new(THREADS));
+        let f = Arc::new(f);
+
+        let handles: Vec<_> = (0..THREADS)
+            .map(|index| {
+                let bus = bus.clone();
+                let barrier = barrier.clone();
+                let f = f.clone();
+
+                thread::spawn(move || {
+                    barrier.wait();
+                    f(&bus, index)
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("thread panicked"))
+            .collect()
+    }
+
+    #[test]
+    fn storage_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<StressBus>();
+    }
+
+    #[test]
+    fn concurrent_take_rx_once() {
+        for _ in 0..20 {
+            let bus = Arc::new(StressBus::default());
+            let results = concurrently(&bus, |bus, _| bus.rx::<MpscMessage>().is_ok());
+
+            let taken = results.into_iter().filter(|ok| *ok).count();
+            assert_eq!(1, taken);
+        }
+    }
+
+    #[test]
+    fn concurrent_clone_tx_links_one_channel() {
+        let bus = Arc::new(StressBus::default());
+        bus.capacity::<MpscMessage>(THREADS).unwrap();
+
+        let senders = concurrently(&bus, |bus, _| bus.tx::<MpscMessage>().unwrap());
+
+        for (index, tx) in senders.iter().enumerate() {
+            tx.try_send(MpscMessage(index)).unwrap();
+        }
+
+        let mut rx = bus.rx::<MpscMessage>().unwrap();
+        let mut received = Vec::new();
+        while let Ok(MpscMessage(index)) = rx.try_recv() {
+            received.push(index);
         }
 
-        drop(state);
+        received.sort_unstable();
+        assert_eq!((0..THREADS).collect::<Vec<_>>(), received);
+    }
+
+    #[test]
+    fn concurrent_clone_broadcast_rx() {
+        let bus = Arc::new(StressBus::default());
+        let receivers = concurrently(&bus, |bus, _| bus.rx::<BroadcastMessage>().unwrap());
+
+        let tx = bus.tx::<BroadcastMessage>().unwrap();
+        tx.send(BroadcastMessage).unwrap();
 
-        let state = self.state.write().unwrap();
-        if state.channels.contains(&id) {
-            return None;
+        for mut rx in receivers {
+            assert!(rx.try_recv().is_ok());
         }
+    }
+
+    #[test]
+    fn concurrent_keyed_channels_are_independent() {
+        let bus = Arc::new(StressBus::default());
+        let results = concurrently(&bus, |bus, index| {
+            let rx = bus.rx_keyed::<MpscMessage, _>(index).is_ok();
+            let tx = bus.tx_keyed::<MpscMessage, _>(index).is_ok();
+            let taken = bus.rx_keyed::<MpscMessage, _>(index).is_err();
+
+            rx && tx && taken
+        });
+
+        assert!(results.into_iter().all(|ok| ok));
+    }
+
+    #[test]
+    fn keyed_rx_taken_twice() {
+        let bus = StressBus::default();
+        let _rx = bus.rx_keyed::<MpscMessage, _>("a").unwrap();
+
+        assert!(bus.rx_keyed::<MpscMessage, _>("a").is_err());
+        assert!(bus.rx_keyed::<MpscMessage, _>("b").is_ok());
+        assert!(bus.rx::<MpscMessage>().is_ok());
+    }
+
+    #[test]
+    fn keyed_capacity_configured_once() {
+        let bus = StressBus::default();
+        bus.capacity_keyed::<MpscMessage, _>("a", 1).unwrap();
+
+        assert!(bus.capacity_keyed::<MpscMessage, _>("a", 2).is_err());
+        assert!(bus.capacity_keyed::<MpscMessage, _>("b", 2).is_ok());
+
+        let tx = bus.tx_keyed::<MpscMessage, _>("a").unwrap();
+        tx.try_send(MpscMessage(1)).unwrap();
+        assert!(tx.try_send(MpscMessage(2)).is_err());
+    }
+
+    #[test]
+    fn capacity_does_not_link() {
+        let bus = StressBus::default();
+        bus.capacity::<MpscMessage>(1).unwrap();
+
+        assert!(!bus.is_linked::<MpscMessage>());
+        assert!(bus.rx_available::<MpscMessage>());
+
+        let _tx = bus.tx::<MpscMessage>().unwrap();
+        assert!(bus.is_linked::<MpscMessage>());
+        assert!(bus.capacity::<MpscMessage>(2).is_err());
+    }
+
+    #[test]
+    fn capacity_rejected_once_linked() {
+        let bus = StressBus::default();
+        let _rx = bus.rx::<MpscMessage>().unwrap();
+
+        assert!(bus.capacity::<MpscMessage>(1).is_err());
+    }
+
+    #[test]
+    fn keyed_tx_closed_when_rx_dropped() {
+        let bus = StressBus::default();
+        let tx = bus.tx_keyed::<MpscMessage, _>(1usize).unwrap();
+        let rx = bus.rx_keyed::<MpscMessage, _>(1usize).unwrap();
+        let other = bus.tx_keyed::<MpscMessage, _>(2usize).unwrap();
+
+        drop(rx);
+        assert!(tx.is_closed());
+        assert!(!other.is_closed());
+    }
+
+    #[test]
+    fn concurrent_capacity_configured_once() {
+        for _ in 0..20 {
+            let bus = Arc::new(StressBus::default());
+            let results = concurrently(&bus, |bus, index| {
+                bus.capacity::<MpscMessage>(index + 1).is_ok()
+            });
+
+            let configured = results.into_iter().filter(|ok| *ok).count();
+            assert_eq!(1, configured);
+        }
+    }
+
+    #[test]
+    fn concurrent_relink() {
+        let bus = Arc::new(StressBus::default());
+        bus.auto_relink(true);
+
+        // each thread takes the receiver, checks that it's connected to the bus sender, and drops it.
+        // auto-relink should always provide a fresh receiver once the previous receiver is dropped.
+        let results = concurrently(&bus, |bus, index| {
+            let mut taken = 0;
+
+            for _ in 0..100 {
+                // another thread may be holding the receiver
+                if let Ok(mut rx) = bus.rx::<MpscMessage>() {
+                    let tx = bus.tx::<MpscMessage>().unwrap();
+                    tx.try_send(MpscMessage(index)).unwrap();
+
+                    assert!(matches!(rx.try_recv(), Ok(MpscMessage(i)) if i == index));
+                    taken += 1;
+                }
+            }
+
+            taken
+        });
+
+        let taken: usize = results.into_iter().sum();
+        assert!(taken > 0);
+        assert!(bus.rx::<MpscMessage>().is_ok());
+    }
+
+    #[test]
+    fn relink_after_drop_closes_old_tx() {
+        let bus = StressBus::default();
+        let old_tx = bus.tx::<MpscMessage>().unwrap();
+        let old_rx = bus.rx::<MpscMessage>().unwrap();
+
+        drop(old_rx);
+        bus.relink::<MpscMessage>();
+        assert!(old_tx.is_closed());
+        assert!(old_tx.try_send(MpscMessage(0)).is_err());
+
+        let tx = bus.tx::<MpscMessage>().unwrap();
+        let mut rx = bus.rx::<MpscMessage>().unwrap();
+        assert!(!tx.same_channel(&old_tx));
+
+        tx.try_send(MpscMessage(1)).unwrap();
+        assert!(matches!(rx.try_recv(), Ok(MpscMessage(1))));
+    }
+
+    #[test]
+    fn relink_drops_stored_rx() {
+        let bus = StressBus::default();
+        let old_tx = bus.tx::<MpscMessage>().unwrap();
+
+        // the receiver was never taken, so relink drops it and closes the old sender
+        bus.relink::<MpscMessage>();
+        assert!(old_tx.is_closed());
+        assert!(!bus.tx::<MpscMessage>().unwrap().is_closed());
+    }
+
+    #[test]
+    fn auto_relink_after_drop() {
+        let bus = StressBus::default();
+        bus.auto_relink(true);
+
+        let old_tx = bus.tx::<MpscMessage>().unwrap();
+        let old_rx = bus.rx::<MpscMessage>().unwrap();
+        assert!(bus.rx::<MpscMessage>().is_err());
+
+        drop(old_rx);
+        assert!(old_tx.is_closed());
+
+        let mut rx = bus.rx::<MpscMessage>().unwrap();
+        let tx = bus.tx::<MpscMessage>().unwrap();
+        tx.try_send(MpscMessage(1)).unwrap();
+
+        assert!(matches!(rx.try_recv(), Ok(MpscMessage(1))));
+        assert!(old_tx.is_closed());
+    }
+
+    #[test]
+    fn poisoned_lock_is_recovered() {
+        let bus = Arc::new(StressBus::default());
+        bus.tx::<MpscMessage>().unwrap();
+
+        let poison = bus.clone();
+        let result = thread::spawn(move || {
+            let _guard = poison
+                .storage()
+                .lock_channel(&ChannelId::of::<MpscMessage>());
+            panic!("poison the lock");
+        })
+        .join();
+
+        assert!(result.is_err());
+        assert!(bus.rx::<MpscMessage>().is_ok());
+        assert!(bus.tx::<MpscMessage>().is_ok());
+    }
+
+    #[test]
+    fn channels_are_constructed_without_holding_the_lock() {
+        let bus = Arc::new(StressBus::default());
+
+        let fragile = bus.clone();
+        let result = thread::spawn(move || fragile.rx::<FragileMessage>()).join();
+        assert!(result.is_err());
+
+        let shard = DynBusStorage::<StressBus>::shard(&ChannelId::of::<FragileMessage>());
+        assert!(!bus.storage().channels[shard].is_poisoned());
+        assert!(!bus.is_linked::<FragileMessage>());
 
-        Some(state)
+        assert!(bus.rx::<FragileMessage>().is_ok());
+        assert!(bus.tx::<FragileMessage>().is_ok());
     }
 }
@@ -2,7 +2,11 @@
 
 use crate::Link;
 use regex::Regex;
-use std::fmt::Debug;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{OnceLock, PoisonError, RwLock},
+};
 use thiserror::Error;
 
 /// Utility function which turns an error into it's debug message as an anyhow::Error.
@@ -22,13 +26,67 @@ pub enum SendError<T: Debug> {
     /// The channel has been closed, but no value was returned
     #[error("channel closed")]
     Closed,
+
+    /// The send timed out while waiting for channel capacity, and the value is returned
+    #[error("send timed out, message: {0:?}")]
+    Timeout(T),
+
+    /// The channel is full, and the backpressure policy rejected the message.  The value is returned
+    #[error("channel full, message: {0:?}")]
+    Full(T),
 }
 
-pub(crate) fn type_name<T>() -> String {
+/// Returns the type name of `T`, without module paths.
+///
+/// Names are computed once for each type, and cached for the lifetime of the program.
+pub(crate) fn type_name<T: ?Sized>() -> &'static str {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    static NAMES: OnceLock<RwLock<HashMap<&'static str, &'static str>>> = OnceLock::new();
+
     let name = std::any::type_name::<T>();
+    let names = NAMES.get_or_init(Default::default);
+
+    if let Some(short) = names
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(name)
+    {
+        return short;
+    }
+
+    let regex = REGEX.get_or_init(|| Regex::new("[a-z][A-Za-z0-9_]+::").expect("Regex compiles"));
+    let short = regex.replace_all(name, "").into_owned();
+
+    // the number of types is bounded, so each name is leaked once
+    names
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(name)
+        .or_insert_with(|| Box::leak(short.into_boxed_str()))
+}
+
+/// An error produced when calling `lifeline::Sender::try_send`.  The value is returned to the caller.
+#[derive(Error, Debug, PartialEq)]
+pub enum TrySendError<T: Debug> {
+    /// The channel is full, and the value could not be sent without waiting
+    #[error("channel full, message: {0:?}")]
+    Full(T),
 
-    let regex = Regex::new("[a-z][A-Za-z0-9_]+::").expect("Regex compiles");
-    regex.replace_all(name, "").to_string()
+    /// The channel has been closed
+    #[error("channel closed, message: {0:?}")]
+    Closed(T),
+}
+
+/// An error produced when calling `lifeline::Receiver::try_recv`
+#[derive(Error, Debug, PartialEq)]
+pub enum TryRecvError {
+    /// The channel is empty, but it may receive messages in the future
+    #[error("channel empty")]
+    Empty,
+
+    /// The channel is empty, and has been closed
+    #[error("channel closed")]
+    Closed,
 }
 
 /// An error produced when attempting to take a Sender or Receiver from the bus.
@@ -188,3 +246,67 @@ impl ResourceInitializedError {
         }
     }
 }
+
+/// An error produced when loading or applying a [CapacityConfig](../config/struct.CapacityConfig.html)
+#[derive(Error, Debug)]
+pub enum CapacityConfigError {
+    /// The configuration source could not be parsed
+    #[error("invalid capacity config: {0}")]
+    Parse(String),
+
+    /// The configuration contains message names which were not applied to the bus
+    #[error("{0}")]
+    UnknownMessages(UnknownMessagesError),
+
+    /// The capacity could not be configured, because the channel was already linked, or its capacity was already configured
+    #[error("{0}")]
+    AlreadyLinked(AlreadyLinkedError),
+
+    /// Two listed message types have the same type name, so the configured capacity is ambiguous
+    #[error("{0}")]
+    DuplicateMessage(DuplicateMessageError),
+}
+
+impl CapacityConfigError {
+    pub fn parse(message: impl Into<String>) -> Self {
+        CapacityConfigError::Parse(message.into())
+    }
+
+    pub fn unknown_messages<Bus>(messages: Vec<String>) -> Self {
+        CapacityConfigError::UnknownMessages(UnknownMessagesError::new::<Bus>(messages))
+    }
+}
+
+/// Two message types listed in the capacity config have the same type name
+#[derive(Error, Debug)]
+#[error("duplicate message name in capacity config: {bus} < {message} >")]
+pub struct DuplicateMessageError {
+    pub bus: String,
+    pub message: String,
+}
+
+impl DuplicateMessageError {
+    pub fn new<Bus, Message>() -> Self {
+        DuplicateMessageError {
+            bus: type_name::<Bus>().to_string(),
+            message: type_name::<Message>().to_string(),
+        }
+    }
+}
+
+/// The capacity config contains message type names which are not carried by the bus
+#[derive(Error, Debug)]
+#[error("unknown messages in capacity config: {bus} < {} >", messages.join(", "))]
+pub struct UnknownMessagesError {
+    pub bus: String,
+    pub messages: Vec<String>,
+}
+
+impl UnknownMessagesError {
+    pub fn new<Bus>(messages: Vec<String>) -> Self {
+        UnknownMessagesError {
+            bus: type_name::<Bus>().to_string(),
+            messages,
+        }
+    }
+}
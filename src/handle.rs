@@ -0,0 +1,198 @@
+use crate::Bus;
+use std::{
+    fmt::Debug,
+    ops::Deref,
+    sync::{Arc, Weak},
+};
+
+/// A clonable, shared handle to a [Bus](./trait.Bus.html).
+///
+/// Services receive `&Bus` only during [Service::spawn](./trait.Service.html#tymethod.spawn).  Tasks which spawn services later
+/// (for example, on each incoming connection) can hold a `BusHandle`, and take channels and resources at any time.
+///
+/// The handle dereferences to the bus, so all `Bus` methods can be called directly.
+/// The bus is dropped when the last strong handle is dropped.  [WeakBusHandle](./struct.WeakBusHandle.html) can be used to avoid keeping channels alive.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::BusHandle;
+/// use tokio::sync::mpsc;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// struct ExampleMessage {}
+/// impl Message<ExampleBus> for ExampleMessage {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let bus = BusHandle::new(ExampleBus::default());
+///     let mut rx = bus.rx::<ExampleMessage>()?;
+///
+///     let handle = bus.clone();
+///     let task = tokio::spawn(async move {
+///         let tx = handle.tx::<ExampleMessage>().unwrap();
+///         tx.send(ExampleMessage {}).await.unwrap();
+///     });
+///
+///     task.await?;
+///     assert!(rx.recv().await.is_some());
+///     Ok(())
+/// }
+/// ```
+pub struct BusHandle<B> {
+    bus: Arc<B>,
+}
+
+impl<B: Bus> BusHandle<B> {
+    /// Moves the bus into a new shared handle.
+    pub fn new(bus: B) -> Self {
+        Self { bus: Arc::new(bus) }
+    }
+
+    /// Creates a weak handle to the bus, which does not keep the bus (or its stored channels) alive.
+    pub fn downgrade(&self) -> WeakBusHandle<B> {
+        WeakBusHandle {
+            bus: Arc::downgrade(&self.bus),
+        }
+    }
+}
+
+impl<B: Bus> Default for BusHandle<B> {
+    fn default() -> Self {
+        Self::new(B::default())
+    }
+}
+
+impl<B: Bus> From<B> for BusHandle<B> {
+    fn from(bus: B) -> Self {
+        Self::new(bus)
+    }
+}
+
+impl<B> Clone for BusHandle<B> {
+    fn clone(&self) -> Self {
+        Self {
+            bus: self.bus.clone(),
+        }
+    }
+}
+
+impl<B> Deref for BusHandle<B> {
+    type Target = B;
+
+    fn deref(&self) -> &B {
+        self.bus.as_ref()
+    }
+}
+
+impl<B: Debug> Debug for BusHandle<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BusHandle").field(self.bus.as_ref()).finish()
+    }
+}
+
+/// A weak handle to a [Bus](./trait.Bus.html), created by [BusHandle::downgrade](./struct.BusHandle.html#method.downgrade).
+///
+/// The weak handle does not keep the bus alive.  Once all strong handles are dropped, the channels stored on the bus are dropped,
+/// and [upgrade](#method.upgrade) returns None.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::BusHandle;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// fn main() {
+///     let bus = BusHandle::new(ExampleBus::default());
+///     let weak = bus.downgrade();
+///     assert!(weak.upgrade().is_some());
+///
+///     drop(bus);
+///     assert!(weak.upgrade().is_none());
+/// }
+/// ```
+pub struct WeakBusHandle<B> {
+    bus: Weak<B>,
+}
+
+impl<B> WeakBusHandle<B> {
+    /// Upgrades to a strong handle, if the bus is still alive.
+    pub fn upgrade(&self) -> Option<BusHandle<B>> {
+        self.bus.upgrade().map(|bus| BusHandle { bus })
+    }
+}
+
+impl<B> Clone for WeakBusHandle<B> {
+    fn clone(&self) -> Self {
+        Self {
+            bus: self.bus.clone(),
+        }
+    }
+}
+
+impl<B> Debug for WeakBusHandle<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let alive = self.bus.strong_count() > 0;
+        f.debug_struct("WeakBusHandle")
+            .field("alive", &alive)
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "tokio-channels"))]
+mod tests {
+    use super::BusHandle;
+    use crate::{lifeline_bus, Bus, Message};
+    use tokio::sync::mpsc;
+
+    lifeline_bus!(struct HandleBus);
+
+    #[derive(Debug)]
+    struct HandleMessage;
+
+    impl Message<HandleBus> for HandleMessage {
+        type Channel = mpsc::Sender<Self>;
+    }
+
+    #[test]
+    fn clones_share_the_bus() {
+        let bus = BusHandle::new(HandleBus::default());
+        let handle = bus.clone();
+
+        let _rx = handle.rx::<HandleMessage>().unwrap();
+        assert!(bus.rx::<HandleMessage>().is_err());
+    }
+
+    #[test]
+    fn upgrade_fails_once_bus_dropped() {
+        let bus = BusHandle::new(HandleBus::default());
+        let weak = bus.downgrade();
+        let weak_clone = weak.clone();
+
+        let tx = weak.upgrade().unwrap().tx::<HandleMessage>().unwrap();
+        assert!(!tx.is_closed());
+
+        drop(bus);
+        assert!(weak.upgrade().is_none());
+        assert!(weak_clone.upgrade().is_none());
+
+        // the stored receiver is dropped with the bus
+        assert!(tx.is_closed());
+    }
+
+    #[test]
+    fn taken_endpoints_outlive_the_bus() {
+        let bus = BusHandle::from(HandleBus::default());
+        let tx = bus.tx::<HandleMessage>().unwrap();
+        let mut rx = bus.rx::<HandleMessage>().unwrap();
+        drop(bus);
+
+        tx.try_send(HandleMessage).unwrap();
+        assert!(rx.try_recv().is_ok());
+    }
+}
@@ -0,0 +1,609 @@
+//! Bus-level message interceptors, which observe (or modify the delivery of) every message sent on the bus.
+//!
+//! Interceptors are registered on the bus with [DynBus::add_interceptor](../dyn_bus/trait.DynBus.html#method.add_interceptor).
+//! Channels are intercepted if their channel type is wrapped in [InterceptSender](./struct.InterceptSender.html) (or if it's a [PrioritySender](../struct.PrioritySender.html)).
+//! When the bus links the channel, it attaches the interceptors to the Sender, and each message passes through the hooks in the sending task, before it is sent.
+//! Interceptors which are added after the channel is linked also apply.  Messages on other channels are not intercepted.
+//!
+//! The bus can also set a default send timeout with [DynBus::set_send_timeout](../dyn_bus/trait.DynBus.html#method.set_send_timeout), which applies to the same channels.
+//! Sends which wait longer than the timeout return `SendError::Timeout(message)`, and log a warning with the message type and the name of the sending task.
+//!
+//! ## Example:
+//! ```
+//! use lifeline::prelude::*;
+//! use lifeline::intercept::{Action, InterceptSender};
+//! use tokio::sync::mpsc;
+//!
+//! lifeline_bus!(pub struct ExampleBus);
+//!
+//! #[derive(Debug)]
+//! struct ExampleMessage(usize);
+//! impl Message<ExampleBus> for ExampleMessage {
+//!     type Channel = InterceptSender<mpsc::Sender<Self>>;
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     use lifeline::{Receiver, Sender};
+//!
+//!     let bus = ExampleBus::default();
+//!     bus.add_interceptor(|type_name: &str, message: &dyn std::fmt::Debug| {
+//!         log::info!("{} sent: {:?}", type_name, message);
+//!         Action::Pass
+//!     });
+//!
+//!     let mut tx = bus.tx::<ExampleMessage>()?;
+//!     let mut rx = bus.rx::<ExampleMessage>()?;
+//!
+//!     tx.send(ExampleMessage(1)).await?;
+//!     assert!(rx.recv().await.is_some());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    backpressure::{BackpressurePolicy, DropTracker, SingleConsumer},
+    error::{type_name, SendError, TrySendError},
+    lag::LagTracker,
+    spawn::{current_task_name, sleep},
+    Channel, Sender, SharedSender, Storage,
+};
+use async_trait::async_trait;
+use log::{trace, warn};
+use std::{
+    fmt::Debug,
+    sync::{Arc, PoisonError, RwLock},
+    time::Duration,
+};
+
+/// The action an [Interceptor](./trait.Interceptor.html) takes on a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Passes the message to the next interceptor, and then to the channel
+    Pass,
+    /// Drops the message.  The send call returns Ok, and the message is never delivered.
+    Drop,
+    /// Delays the message for the duration, using the timer of the enabled executor, and then passes it to the next interceptor
+    Delay(Duration),
+}
+
+/// A hook which is called for every message sent through an [InterceptSender](./struct.InterceptSender.html).
+///
+/// The hook receives the message type name (without module paths), and the message value.
+/// Interceptors are implemented for closures of the form `Fn(&str, &dyn Debug) -> Action`.
+pub trait Interceptor: Send + Sync + 'static {
+    /// Inspects the message, and returns the action to take.
+    fn intercept(&self, type_name: &str, message: &dyn Debug) -> Action;
+}
+
+impl<F> Interceptor for F
+where
+    F: Fn(&str, &dyn Debug) -> Action + Send + Sync + 'static,
+{
+    fn intercept(&self, type_name: &str, message: &dyn Debug) -> Action {
+        self(type_name, message)
+    }
+}
+
+/// A shared, ordered list of interceptors, and the default send timeout.
+///
+/// The list is shared between the bus and all intercepted senders, so interceptors which are added later apply to channels which are already linked.
+#[derive(Clone, Default)]
+pub struct Interceptors {
+    hooks: Arc<RwLock<Vec<Arc<dyn Interceptor>>>>,
+    send_timeout: Arc<RwLock<Option<Duration>>>,
+}
+
+impl Interceptors {
+    /// Adds an interceptor.  Interceptors run in the order they were added.
+    pub fn add(&self, interceptor: impl Interceptor) {
+        self.hooks
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::new(interceptor));
+    }
+
+    /// Returns true if no interceptors have been added.
+    pub fn is_empty(&self) -> bool {
+        self.hooks
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty()
+    }
+
+    /// Sets the default timeout for intercepted sends.  If `None`, sends wait for channel capacity indefinitely.
+    pub fn set_send_timeout(&self, timeout: Option<Duration>) {
+        *self
+            .send_timeout
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = timeout;
+    }
+
+    /// Returns the default timeout for intercepted sends.
+    pub fn send_timeout(&self) -> Option<Duration> {
+        *self
+            .send_timeout
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs the interceptors on the message, in order.
+    ///
+    /// If any interceptor drops the message, returns `Action::Drop` without running the remaining interceptors.
+    /// Otherwise, returns the sum of the requested delays (or `Action::Pass` if there are none).
+    pub fn run(&self, type_name: &str, message: &dyn Debug) -> Action {
+        let hooks = self.hooks.read().unwrap_or_else(PoisonError::into_inner);
+
+        let mut delay = None;
+        for hook in hooks.iter() {
+            match hook.intercept(type_name, message) {
+                Action::Pass => {}
+                Action::Drop => return Action::Drop,
+                Action::Delay(duration) => {
+                    delay = Some(delay.unwrap_or_default() + duration);
+                }
+            }
+        }
+
+        delay.map(Action::Delay).unwrap_or(Action::Pass)
+    }
+
+    /// Runs the interceptors on the message, and logs the action.
+    pub(crate) fn intercept<T: Debug>(&self, value: &T) -> Action {
+        if self.is_empty() {
+            return Action::Pass;
+        }
+
+        let action = self.run(type_name::<T>(), value);
+        match action {
+            Action::Pass => {}
+            Action::Drop => trace!("{} dropped by interceptor", type_name::<T>()),
+            Action::Delay(duration) => trace!(
+                "{} delayed by interceptor: {:?}",
+                type_name::<T>(),
+                duration
+            ),
+        }
+
+        action
+    }
+}
+
+impl Debug for Interceptors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let len = self
+            .hooks
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len();
+
+        f.debug_struct("Interceptors")
+            .field("len", &len)
+            .field("send_timeout", &self.send_timeout())
+            .finish()
+    }
+}
+
+/// A sender wrapper which passes each message through the bus interceptors before sending it.
+///
+/// `InterceptSender<Chan>` is also a [Channel](../trait.Channel.html), which wraps the Sender of `Chan` and leaves the Receiver unchanged.
+/// The interceptors run in the sending task, and interceptors which are added after the channel is linked also apply.
+///
+/// ## Example:
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::intercept::{Action, InterceptSender};
+/// use tokio::sync::mpsc;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// struct ExampleMessage(usize);
+/// impl Message<ExampleBus> for ExampleMessage {
+///     type Channel = InterceptSender<mpsc::Sender<Self>>;
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     use lifeline::{Receiver, Sender};
+///
+///     let bus = ExampleBus::default();
+///     let mut tx = bus.tx::<ExampleMessage>()?;
+///     let mut rx = bus.rx::<ExampleMessage>()?;
+///
+///     // the channel is already linked, but the interceptor still applies
+///     bus.add_interceptor(|_type_name: &str, message: &dyn std::fmt::Debug| {
+///         match format!("{:?}", message).as_str() {
+///             "ExampleMessage(1)" => Action::Drop,
+///             _ => Action::Pass,
+///         }
+///     });
+///
+///     tx.send(ExampleMessage(1)).await?;
+///     tx.send(ExampleMessage(2)).await?;
+///     assert_eq!("ExampleMessage(2)", format!("{:?}", rx.recv().await.unwrap()));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct InterceptSender<S> {
+    inner: S,
+    interceptors: Interceptors,
+}
+
+impl<S> InterceptSender<S> {
+    /// Wraps the sender.  The sender does not run any interceptors until the bus links it.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            interceptors: Interceptors::default(),
+        }
+    }
+
+    /// Returns a reference to the wrapped sender.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns the wrapped sender.  Messages sent on the returned sender are not intercepted.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Takes the wrapped sender from the option using the provided function, and copies the interceptors to the result.
+    fn map_slot(
+        slot: &mut Option<Self>,
+        f: impl FnOnce(&mut Option<S>) -> Option<S>,
+    ) -> Option<Self> {
+        let InterceptSender {
+            inner,
+            interceptors,
+        } = slot.take()?;
+
+        let mut inner = Some(inner);
+        let taken = f(&mut inner);
+
+        *slot = inner.map(|inner| InterceptSender {
+            inner,
+            interceptors: interceptors.clone(),
+        });
+
+        taken.map(|inner| InterceptSender {
+            inner,
+            interceptors,
+        })
+    }
+}
+
+impl<S: Storage> Storage for InterceptSender<S> {
+    fn take_or_clone(res: &mut Option<Self>) -> Option<Self> {
+        Self::map_slot(res, S::take_or_clone)
+    }
+}
+
+impl<Chan: Channel> Channel for InterceptSender<Chan> {
+    type Tx = InterceptSender<Chan::Tx>;
+    type Rx = Chan::Rx;
+
+    fn channel(capacity: usize) -> (Self::Tx, Self::Rx) {
+        let (tx, rx) = Chan::channel(capacity);
+        (InterceptSender::new(tx), rx)
+    }
+
+    fn default_capacity() -> usize {
+        Chan::default_capacity()
+    }
+
+    fn clone_tx(tx: &mut Option<Self::Tx>) -> Option<Self::Tx> {
+        InterceptSender::map_slot(tx, Chan::clone_tx)
+    }
+
+    fn clone_rx(rx: &mut Option<Self::Rx>, tx: Option<&Self::Tx>) -> Option<Self::Rx> {
+        Chan::clone_rx(rx, tx.map(InterceptSender::get_ref))
+    }
+
+    fn rx_available(rx: Option<&Self::Rx>, tx: Option<&Self::Tx>) -> bool {
+        Chan::rx_available(rx, tx.map(InterceptSender::get_ref))
+    }
+
+    fn is_closed(tx: Option<&Self::Tx>, rx: Option<&Self::Rx>) -> bool {
+        Chan::is_closed(tx.map(InterceptSender::get_ref), rx)
+    }
+
+    fn intercept(tx: &mut Self::Tx, interceptors: &Interceptors) {
+        tx.interceptors = interceptors.clone();
+    }
+
+    fn track_lag(rx: &mut Self::Rx, tracker: &LagTracker) {
+        Chan::track_lag(rx, tracker);
+    }
+
+    fn backpressure(tx: &mut Self::Tx, policy: BackpressurePolicy, drops: &DropTracker) {
+        Chan::backpressure(&mut tx.inner, policy, drops);
+    }
+}
+
+impl<Chan: SingleConsumer> SingleConsumer for InterceptSender<Chan> {}
+
+/// Logs a warning if the send timed out
+pub(crate) fn warn_timeout<T: Debug>(result: &Result<(), SendError<T>>, timeout: Duration) {
+    if let Err(SendError::Timeout(_)) = result {
+        let task = current_task_name();
+        warn!(
+            "{} send blocked for {:?} in task {}",
+            type_name::<T>(),
+            timeout,
+            task.as_deref().unwrap_or("<unknown>")
+        );
+    }
+}
+
+#[async_trait]
+impl<T, S> Sender<T> for InterceptSender<S>
+where
+    T: Debug + Send + 'static,
+    S: Sender<T> + Send,
+{
+    async fn send(&mut self, value: T) -> Result<(), SendError<T>> {
+        match self.interceptors.intercept(&value) {
+            Action::Pass => {}
+            Action::Drop => return Ok(()),
+            Action::Delay(duration) => sleep(duration).await,
+        }
+
+        match self.interceptors.send_timeout() {
+            Some(timeout) => {
+                let result = self.inner.send_timeout(value, timeout).await;
+                warn_timeout(&result, timeout);
+                result
+            }
+            None => self.inner.send(value).await,
+        }
+    }
+
+    async fn send_timeout(&mut self, value: T, timeout: Duration) -> Result<(), SendError<T>> {
+        match self.interceptors.intercept(&value) {
+            Action::Pass => {}
+            Action::Drop => return Ok(()),
+            Action::Delay(duration) => sleep(duration).await,
+        }
+
+        let result = self.inner.send_timeout(value, timeout).await;
+        warn_timeout(&result, timeout);
+        result
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        // try_send can't wait, so delays requested by interceptors are skipped
+        if let Action::Drop = self.interceptors.intercept(&value) {
+            return Ok(());
+        }
+
+        self.inner.try_send(value)
+    }
+}
+
+/// Shared sends run the interceptors, but don't apply the bus default send timeout.
+#[async_trait]
+impl<T, S> SharedSender<T> for InterceptSender<S>
+where
+    T: Debug + Send + 'static,
+    S: SharedSender<T> + Send + Sync,
+{
+    async fn send_shared(&self, value: T) -> Result<(), SendError<T>> {
+        match self.interceptors.intercept(&value) {
+            Action::Pass => {}
+            Action::Drop => return Ok(()),
+            Action::Delay(duration) => sleep(duration).await,
+        }
+
+        self.inner.send_shared(value).await
+    }
+
+    fn try_send_shared(&self, value: T) -> Result<(), TrySendError<T>> {
+        if let Action::Drop = self.interceptors.intercept(&value) {
+            return Ok(());
+        }
+
+        self.inner.try_send_shared(value)
+    }
+}
+
+#[cfg(all(test, feature = "tokio-channels"))]
+mod tests {
+    use super::{Action, InterceptSender};
+    use crate::{
+        dyn_bus::DynBus,
+        error::{SendError, TrySendError},
+        lifeline_bus, Bus, Message, Priority, PrioritySender, SharedSender,
+    };
+    use std::{fmt::Debug, time::Duration};
+    use tokio::sync::mpsc;
+
+    lifeline_bus!(struct InterceptBus);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Number(usize);
+
+    impl Message<InterceptBus> for Number {
+        type Channel = mpsc::Sender<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Work(usize);
+
+    impl Priority for Work {}
+
+    impl Message<InterceptBus> for Work {
+        type Channel = PrioritySender<Self>;
+    }
+
+    /// Drops messages which contain an odd number
+    fn drop_odd(_type_name: &str, message: &dyn Debug) -> Action {
+        let odd = format!("{:?}", message)
+            .trim_end_matches(')')
+            .rsplit('(')
+            .next()
+            .and_then(|n| n.parse::<usize>().ok())
+            .map(|n| n % 2 == 1)
+            .unwrap_or(false);
+
+        if odd {
+            Action::Drop
+        } else {
+            Action::Pass
+        }
+    }
+
+    #[tokio::test]
+    async fn plain_channels_are_not_intercepted() {
+        let bus = InterceptBus::default();
+        bus.add_interceptor(drop_odd);
+
+        let tx = bus.tx::<Number>().unwrap();
+        let mut rx = bus.rx::<Number>().unwrap();
+
+        tx.send(Number(1)).await.unwrap();
+        assert_eq!(Some(Number(1)), rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn priority_sender_runs_interceptors() {
+        use crate::{Receiver, Sender};
+
+        let bus = InterceptBus::default();
+        let mut tx = bus.tx::<Work>().unwrap();
+        let mut rx = bus.rx::<Work>().unwrap();
+
+        // the priority sender runs the interceptors itself, so interceptors added later apply
+        bus.add_interceptor(drop_odd);
+
+        tx.send(Work(1)).await.unwrap();
+        tx.send(Work(2)).await.unwrap();
+        tx.try_send(Work(3)).unwrap();
+        drop(tx);
+        drop(bus);
+
+        assert_eq!(Some(Work(2)), rx.recv().await);
+        assert_eq!(None, rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn priority_sender_applies_send_timeout() {
+        use crate::{error::SendError, Sender};
+
+        let bus = InterceptBus::default();
+        bus.capacity::<Work>(1).unwrap();
+        bus.set_send_timeout(Some(Duration::from_millis(5)));
+
+        let mut tx = bus.tx::<Work>().unwrap();
+        let _rx = bus.rx::<Work>().unwrap();
+
+        tx.send(Work(2)).await.unwrap();
+        assert_eq!(Err(SendError::Timeout(Work(4))), tx.send(Work(4)).await);
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Intercepted(usize);
+
+    impl Message<InterceptBus> for Intercepted {
+        type Channel = InterceptSender<mpsc::Sender<Self>>;
+    }
+
+    #[tokio::test]
+    async fn intercept_sender_shared_sends_run_interceptors() {
+        let bus = InterceptBus::default();
+        let tx = bus.tx::<Intercepted>().unwrap();
+        let mut rx = bus.rx::<Intercepted>().unwrap();
+        bus.add_interceptor(drop_odd);
+
+        for n in 0..4 {
+            tx.send_shared(Intercepted(n)).await.unwrap();
+        }
+        tx.try_send_shared(Intercepted(5)).unwrap();
+        tx.try_send_shared(Intercepted(6)).unwrap();
+        drop(tx);
+        drop(bus);
+
+        let mut received = Vec::new();
+        while let Some(Intercepted(n)) = rx.recv().await {
+            received.push(n);
+        }
+
+        assert_eq!(vec![0, 2, 6], received);
+    }
+
+    #[tokio::test]
+    async fn intercept_sender_shared_sends_with_dropped_receiver() {
+        let bus = InterceptBus::default();
+        let tx = bus.tx::<Intercepted>().unwrap();
+        drop(bus.rx::<Intercepted>().unwrap());
+        bus.add_interceptor(drop_odd);
+        drop(bus);
+
+        assert_eq!(
+            Err(SendError::Return(Intercepted(2))),
+            tx.send_shared(Intercepted(2)).await
+        );
+        assert_eq!(
+            Err(TrySendError::Closed(Intercepted(4))),
+            tx.try_send_shared(Intercepted(4))
+        );
+
+        // dropped messages are never sent, so they don't observe the closed channel
+        assert_eq!(Ok(()), tx.send_shared(Intercepted(1)).await);
+    }
+
+    #[tokio::test]
+    async fn intercept_sender_keeps_channel_capacity() {
+        let bus = InterceptBus::default();
+        bus.capacity::<Intercepted>(1).unwrap();
+        bus.add_interceptor(drop_odd);
+
+        let tx = bus.tx::<Intercepted>().unwrap();
+        let mut rx = bus.rx::<Intercepted>().unwrap();
+
+        tx.try_send_shared(Intercepted(2)).unwrap();
+        assert_eq!(
+            Err(TrySendError::Full(Intercepted(4))),
+            tx.try_send_shared(Intercepted(4))
+        );
+
+        // the dropped message is never sent, so it doesn't observe the full channel
+        assert_eq!(Ok(()), tx.try_send_shared(Intercepted(1)));
+        assert_eq!(Some(Intercepted(2)), rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn intercept_sender_applies_send_timeout() {
+        use crate::Sender;
+
+        let bus = InterceptBus::default();
+        bus.capacity::<Intercepted>(1).unwrap();
+
+        let mut tx = bus.tx::<Intercepted>().unwrap();
+        let _rx = bus.rx::<Intercepted>().unwrap();
+
+        // the timeout is shared with the linked sender, so it applies when it's set later
+        bus.set_send_timeout(Some(Duration::from_millis(5)));
+
+        tx.send(Intercepted(1)).await.unwrap();
+        assert_eq!(
+            Err(SendError::Timeout(Intercepted(2))),
+            tx.send(Intercepted(2)).await
+        );
+    }
+
+    #[test]
+    fn intercept_sender_runs_outside_runtime() {
+        let bus = InterceptBus::default();
+        bus.add_interceptor(drop_odd);
+
+        let tx = bus.tx::<Intercepted>().unwrap();
+        let mut rx = bus.rx::<Intercepted>().unwrap();
+
+        tx.try_send_shared(Intercepted(1)).unwrap();
+        tx.try_send_shared(Intercepted(2)).unwrap();
+        assert_eq!(Some(Intercepted(2)), rx.try_recv().ok());
+    }
+}
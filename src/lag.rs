@@ -0,0 +1,472 @@
+//! Lag tracking for broadcast channels, which drop the oldest messages when a receiver falls behind.
+//!
+//! By default, lifeline receivers skip lagged messages, and write a debug log.
+//! [Receiver::recv_lossy](../trait.Receiver.html#method.recv_lossy) surfaces the lag to the caller as `Recv::Lagged(n)`.
+//!
+//! Channels can also opt in to bus-level lag tracking, by wrapping the channel type in [TrackLag](./struct.TrackLag.html).
+//! The bus counts lagged messages for each message type, and applies the bus [LagPolicy](./enum.LagPolicy.html) when `recv` observes a lag:
+//! ```
+//! use lifeline::prelude::*;
+//! use lifeline::lag::{LagPolicy, TrackLag};
+//! use tokio::sync::broadcast;
+//!
+//! lifeline_bus!(pub struct ExampleBus);
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! enum ExampleState {
+//!     Update(usize),
+//!     Snapshot,
+//! }
+//!
+//! impl Message<ExampleBus> for ExampleState {
+//!     type Channel = TrackLag<broadcast::Sender<Self>>;
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     use lifeline::Receiver;
+//!
+//!     let bus = ExampleBus::default();
+//!     bus.capacity::<ExampleState>(2)?;
+//!     bus.set_lag_policy(LagPolicy::Resync);
+//!     bus.lag_snapshot::<ExampleState>(|| ExampleState::Snapshot);
+//!
+//!     let mut rx = bus.rx::<ExampleState>()?;
+//!     let tx = bus.tx::<ExampleState>()?;
+//!
+//!     for i in 0..4 {
+//!         tx.send(ExampleState::Update(i))?;
+//!     }
+//!
+//!     // the receiver lagged, so it's resynced with the snapshot message
+//!     assert_eq!(Some(ExampleState::Snapshot), rx.recv().await);
+//!     assert_eq!(2, bus.lag_count::<ExampleState>());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    backpressure::{BackpressurePolicy, DropTracker},
+    channel::lifeline::Recv,
+    counts::TypeCounts,
+    error::type_name,
+    intercept::Interceptors,
+    Channel, Receiver, Storage,
+};
+use async_trait::async_trait;
+use log::{error, warn};
+use std::{
+    any::{Any, TypeId},
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    marker::PhantomData,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+/// The action a [LagReceiver](./struct.LagReceiver.html) takes when `recv` observes a lag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LagPolicy {
+    /// Skips the lost messages, and returns the oldest message which is still buffered
+    #[default]
+    Skip,
+    /// Logs an error, and returns `None` from `recv`, which ends the receive loop of the service
+    Error,
+    /// Discards the buffered messages, and returns the snapshot message for the type.
+    /// The snapshot is registered with [DynBus::lag_snapshot](../dyn_bus/trait.DynBus.html#method.lag_snapshot).
+    /// If no snapshot is registered, the lag is skipped.
+    Resync,
+}
+
+type SnapshotFn<T> = Arc<dyn Fn() -> T + Send + Sync>;
+
+/// The lag policy, lag counters, and snapshot messages of a bus.
+///
+/// The tracker is shared between the bus and all lag-tracked receivers, so changes to the policy apply to receivers which have already been taken.
+#[derive(Clone, Default)]
+pub struct LagTracker {
+    policy: Arc<RwLock<LagPolicy>>,
+    counts: TypeCounts,
+    snapshots: Arc<RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync>>>>,
+}
+
+impl LagTracker {
+    /// Sets the lag policy
+    pub fn set_policy(&self, policy: LagPolicy) {
+        *self.policy.write().unwrap_or_else(PoisonError::into_inner) = policy;
+    }
+
+    /// Returns the lag policy
+    pub fn policy(&self) -> LagPolicy {
+        *self.policy.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Sets the function which constructs a snapshot message for the type, used by `LagPolicy::Resync`
+    pub fn set_snapshot<T: 'static>(&self, snapshot: impl Fn() -> T + Send + Sync + 'static) {
+        let snapshot: SnapshotFn<T> = Arc::new(snapshot);
+
+        self.snapshots
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(TypeId::of::<T>(), Box::new(snapshot));
+    }
+
+    /// Constructs a snapshot message for the type, if a snapshot function has been set
+    pub fn snapshot<T: 'static>(&self) -> Option<T> {
+        let snapshot = self
+            .snapshots
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&TypeId::of::<T>())
+            .and_then(|snapshot| snapshot.downcast_ref::<SnapshotFn<T>>())
+            .cloned();
+
+        snapshot.map(|snapshot| snapshot())
+    }
+
+    /// Records lost messages for the type
+    pub fn record<T: 'static>(&self, lagged: u64) {
+        self.counts.add::<T>(lagged);
+    }
+
+    /// Returns the number of messages which have been lost by receivers of the type
+    pub fn count<T: 'static>(&self) -> u64 {
+        self.counts.get::<T>()
+    }
+
+    /// Returns the number of lost messages, for each message type name which has lagged
+    pub fn counts(&self) -> BTreeMap<String, u64> {
+        self.counts.by_name()
+    }
+}
+
+impl Debug for LagTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LagTracker")
+            .field("policy", &self.policy())
+            .field("counts", &self.counts())
+            .finish()
+    }
+}
+
+/// A channel wrapper which enables lag tracking.  The Sender of `Chan` is unchanged, and the Receiver is wrapped in [LagReceiver](./struct.LagReceiver.html).
+///
+/// Use it as the channel type in a [Message](../trait.Message.html) implementation to track lag for that message.
+#[derive(Debug)]
+pub struct TrackLag<Chan> {
+    _chan: PhantomData<fn() -> Chan>,
+}
+
+impl<Chan: Channel> Channel for TrackLag<Chan> {
+    type Tx = Chan::Tx;
+    type Rx = LagReceiver<Chan::Rx>;
+
+    fn channel(capacity: usize) -> (Self::Tx, Self::Rx) {
+        let (tx, rx) = Chan::channel(capacity);
+        (tx, LagReceiver::new(rx, capacity))
+    }
+
+    fn default_capacity() -> usize {
+        Chan::default_capacity()
+    }
+
+    fn clone_tx(tx: &mut Option<Self::Tx>) -> Option<Self::Tx> {
+        Chan::clone_tx(tx)
+    }
+
+    fn clone_rx(rx: &mut Option<Self::Rx>, tx: Option<&Self::Tx>) -> Option<Self::Rx> {
+        LagReceiver::map_slot(rx, |inner| Chan::clone_rx(inner, tx))
+    }
+
+    fn rx_available(rx: Option<&Self::Rx>, tx: Option<&Self::Tx>) -> bool {
+        Chan::rx_available(rx.map(LagReceiver::get_ref), tx)
+    }
+
+    fn is_closed(tx: Option<&Self::Tx>, rx: Option<&Self::Rx>) -> bool {
+        Chan::is_closed(tx, rx.map(LagReceiver::get_ref))
+    }
+
+    fn intercept(tx: &mut Self::Tx, interceptors: &Interceptors) {
+        Chan::intercept(tx, interceptors);
+    }
+
+    fn track_lag(rx: &mut Self::Rx, tracker: &LagTracker) {
+        rx.tracker = tracker.clone();
+    }
+
+    fn backpressure(tx: &mut Self::Tx, policy: BackpressurePolicy, drops: &DropTracker) {
+        Chan::backpressure(tx, policy, drops);
+    }
+}
+
+/// A receiver wrapper which records lagged messages in the bus [LagTracker](./struct.LagTracker.html), and applies the bus lag policy.
+#[derive(Debug)]
+pub struct LagReceiver<R> {
+    inner: R,
+    tracker: LagTracker,
+    capacity: usize,
+}
+
+impl<R> LagReceiver<R> {
+    /// Wraps the receiver of a channel with the given capacity.  The receiver uses its own tracker (with `LagPolicy::Skip`) until the bus links it.
+    ///
+    /// `LagPolicy::Resync` discards at most `capacity` buffered messages before returning the snapshot.
+    pub fn new(inner: R, capacity: usize) -> Self {
+        Self {
+            inner,
+            tracker: LagTracker::default(),
+            capacity,
+        }
+    }
+
+    /// Returns a reference to the wrapped receiver.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns the wrapped receiver.  Lag on the returned receiver is not tracked.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Takes the wrapped receiver from the option using the provided function, and copies the tracker to the result.
+    fn map_slot(
+        slot: &mut Option<Self>,
+        f: impl FnOnce(&mut Option<R>) -> Option<R>,
+    ) -> Option<Self> {
+        let LagReceiver {
+            inner,
+            tracker,
+            capacity,
+        } = slot.take()?;
+
+        let mut inner = Some(inner);
+        let taken = f(&mut inner);
+
+        *slot = inner.map(|inner| LagReceiver {
+            inner,
+            tracker: tracker.clone(),
+            capacity,
+        });
+
+        taken.map(|inner| LagReceiver {
+            inner,
+            tracker,
+            capacity,
+        })
+    }
+}
+
+impl<R: Storage> Storage for LagReceiver<R> {
+    fn take_or_clone(res: &mut Option<Self>) -> Option<Self> {
+        Self::map_slot(res, R::take_or_clone)
+    }
+}
+
+#[async_trait]
+impl<T, R> Receiver<T> for LagReceiver<R>
+where
+    T: Send + 'static,
+    R: Receiver<T> + Send,
+{
+    async fn recv(&mut self) -> Option<T> {
+        loop {
+            let lagged = match self.inner.recv_lossy().await {
+                Recv::Value(value) => return Some(value),
+                Recv::Closed => return None,
+                Recv::Lagged(lagged) => lagged,
+            };
+
+            self.tracker.record::<T>(lagged);
+
+            match self.tracker.policy() {
+                LagPolicy::Skip => continue,
+                LagPolicy::Error => {
+                    error!(
+                        "{} receiver lagged by {} messages",
+                        type_name::<T>(),
+                        lagged
+                    );
+                    return None;
+                }
+                LagPolicy::Resync => match self.tracker.snapshot::<T>() {
+                    Some(snapshot) => {
+                        // the buffered messages are older than the snapshot.  the drain is bounded,
+                        // as a fast sender can keep the channel full.
+                        for _ in 0..self.capacity {
+                            if self.inner.try_recv().is_err() {
+                                break;
+                            }
+                        }
+                        return Some(snapshot);
+                    }
+                    None => {
+                        warn!("{} has no lag snapshot, skipping lag", type_name::<T>());
+                        continue;
+                    }
+                },
+            }
+        }
+    }
+
+    async fn recv_lossy(&mut self) -> Recv<T> {
+        let received = self.inner.recv_lossy().await;
+
+        if let Recv::Lagged(lagged) = received {
+            self.tracker.record::<T>(lagged);
+        }
+
+        received
+    }
+}
+
+#[cfg(all(test, feature = "tokio-channels"))]
+mod tests {
+    use super::{LagPolicy, LagReceiver, TrackLag};
+    use crate::{
+        channel::lifeline::Recv, dyn_bus::DynBus, error::TryRecvError, lifeline_bus, Bus, Message,
+        Receiver,
+    };
+    use async_trait::async_trait;
+    use tokio::sync::broadcast;
+
+    lifeline_bus!(struct LagBus);
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum State {
+        Update(usize),
+        Snapshot,
+    }
+
+    impl Message<LagBus> for State {
+        type Channel = TrackLag<broadcast::Sender<Self>>;
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Untracked(usize);
+
+    impl Message<LagBus> for Untracked {
+        type Channel = broadcast::Sender<Self>;
+    }
+
+    /// Links a receiver, and sends 4 updates on a channel with capacity 2, which drops the oldest 2
+    fn lagged(bus: &LagBus) -> <TrackLag<broadcast::Sender<State>> as crate::Channel>::Rx {
+        bus.capacity::<State>(2).unwrap();
+        let rx = bus.rx::<State>().unwrap();
+        let tx = bus.tx::<State>().unwrap();
+
+        for i in 0..4 {
+            tx.send(State::Update(i)).unwrap();
+        }
+
+        rx
+    }
+
+    #[tokio::test]
+    async fn skip_returns_oldest_buffered() {
+        let bus = LagBus::default();
+        let mut rx = lagged(&bus);
+
+        assert_eq!(Some(State::Update(2)), rx.recv().await);
+        assert_eq!(Some(State::Update(3)), rx.recv().await);
+        assert_eq!(2, bus.lag_count::<State>());
+    }
+
+    #[tokio::test]
+    async fn error_ends_recv() {
+        let bus = LagBus::default();
+        bus.set_lag_policy(LagPolicy::Error);
+        let mut rx = lagged(&bus);
+
+        assert_eq!(None, rx.recv().await);
+        assert_eq!(2, bus.lag_count::<State>());
+    }
+
+    #[tokio::test]
+    async fn resync_returns_snapshot() {
+        let bus = LagBus::default();
+        bus.set_lag_policy(LagPolicy::Resync);
+        bus.lag_snapshot::<State>(|| State::Snapshot);
+        let mut rx = lagged(&bus);
+
+        assert_eq!(Some(State::Snapshot), rx.recv().await);
+        assert!(rx.try_recv().is_err(), "buffered messages are discarded");
+        assert_eq!(2, bus.lag_count::<State>());
+    }
+
+    /// A receiver which always lags, and always has another message buffered
+    struct Flood {
+        drained: usize,
+    }
+
+    #[async_trait]
+    impl Receiver<State> for Flood {
+        async fn recv(&mut self) -> Option<State> {
+            Some(State::Update(0))
+        }
+
+        fn try_recv(&mut self) -> Result<State, TryRecvError> {
+            self.drained += 1;
+            Ok(State::Update(self.drained))
+        }
+
+        async fn recv_lossy(&mut self) -> Recv<State> {
+            Recv::Lagged(1)
+        }
+    }
+
+    #[tokio::test]
+    async fn resync_drain_is_bounded_by_capacity() {
+        let mut rx = LagReceiver::new(Flood { drained: 0 }, 3);
+        rx.tracker.set_policy(LagPolicy::Resync);
+        rx.tracker.set_snapshot(|| State::Snapshot);
+
+        assert_eq!(Some(State::Snapshot), rx.recv().await);
+        assert_eq!(3, rx.get_ref().drained);
+    }
+
+    #[tokio::test]
+    async fn resync_without_snapshot_skips() {
+        let bus = LagBus::default();
+        bus.set_lag_policy(LagPolicy::Resync);
+        let mut rx = lagged(&bus);
+
+        assert_eq!(Some(State::Update(2)), rx.recv().await);
+        assert_eq!(2, bus.lag_count::<State>());
+    }
+
+    #[tokio::test]
+    async fn policy_applies_to_taken_receivers() {
+        let bus = LagBus::default();
+        let mut rx = lagged(&bus);
+        bus.set_lag_policy(LagPolicy::Error);
+
+        assert_eq!(None, rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn recv_lossy_records_lag() {
+        let bus = LagBus::default();
+        bus.set_lag_policy(LagPolicy::Error);
+        let mut rx = lagged(&bus);
+
+        assert_eq!(Recv::Lagged(2), rx.recv_lossy().await);
+        assert_eq!(Recv::Value(State::Update(2)), rx.recv_lossy().await);
+        assert_eq!(2, bus.lag_count::<State>());
+    }
+
+    #[tokio::test]
+    async fn untracked_channels_are_not_counted() {
+        let bus = LagBus::default();
+        bus.capacity::<Untracked>(2).unwrap();
+        let mut rx = bus.rx::<Untracked>().unwrap();
+        let tx = bus.tx::<Untracked>().unwrap();
+
+        for i in 0..4 {
+            tx.send(Untracked(i)).unwrap();
+        }
+
+        assert_eq!(Some(Untracked(2)), Receiver::recv(&mut rx).await);
+        assert_eq!(0, bus.lag_count::<Untracked>());
+        assert!(bus.lag_counts().is_empty());
+    }
+}
@@ -21,7 +21,8 @@
 //! lifeline = "0.6"
 //! ```
 //!
-//! [async-std](https://docs.rs/async-std/) can be enabled with the `async-std-executor` feature.  And the `mpsc` implementation can be enabled with the `async-std-channels` feature:
+//! [async-std](https://docs.rs/async-std/) can be enabled with the `async-std-executor` feature.  And the async-std channel implementations can be enabled with the `async-std-channels` feature.
+//! This provides `mpsc` (async_std::channel), `broadcast` ([async-broadcast](https://docs.rs/async-broadcast/)), `watch` ([async-watch](https://docs.rs/async-watch/)), and `oneshot` ([futures-channel](https://docs.rs/futures-channel/)) channels:
 //! ```toml
 //! lifeline = { version = "0.6", default-features = false, features = ["dyn-bus", "async-std-executor", "async-std-channels"] }
 //! ```
@@ -30,14 +31,17 @@
 //! Postage also provides Stream and Sink combinators (similar to futures StreamExt), that are optimized for async channels.  
 //! Postage is intended to replace the LifelineSender/LifelineReceiver wrappers that were removed in lifeline v0.6.0.
 //!
+//! [flume channels](https://docs.rs/flume/) can be enabled with the `flume-channels` feature.  Flume receivers are clonable, and flume senders can be used from blocking threads.
+//! Bounded channels use `flume::Sender<Self>`, and unbounded channels use [FlumeUnbounded\<Self\>](./struct.FlumeUnbounded.html).
+//!
 //! ## Upgrading
 //! v0.6.0 contains several breaking changes:
 //! - The LifelineSender and LifelineReceiver wrappers were removed.  This was necessary due to the recent changes in the Stream ecosystem, and the upcoming stabilization of the Stream RFC.
-//! If you need Stream/Sink combinators, take a look at [postage](https://crates.io/crates/postage), or [tokio-stream](https://crates.io/crates/tokio-stream).
+//!   If you need Stream/Sink combinators, take a look at [postage](https://crates.io/crates/postage), or [tokio-stream](https://crates.io/crates/tokio-stream).
 //! - The barrier channel was removed.  It can be replaced with [postage::barrier](https://docs.rs/postage/0.3.1/postage/barrier/index.html).
 //! - The subscription channel was removed.  If you need it back, you can find the code before the removal [here](https://github.com/austinjones/lifeline-rs/blob/b15ab2342abcfa9c553d403cb58d2403531bf89c/src/channel/subscription.rs).
 //! - The Sender and Receiver traits were removed from prelude.   This is so that importing the lifeline prelude does not conflict with Sink/Stream traits.  You can import them with:
-//! `use lifeline::{Sender, Receiver}`.
+//!   `use lifeline::{Sender, Receiver}`.
 //!
 //! ## The Bus
 //! The [Bus](./trait.Bus.html) carries channels and resources, and allows you to write loosely coupled [Service](./trait.Service.html) implementations which communicate over messages.
@@ -52,6 +56,27 @@
 //! lifeline_bus!(pub struct MainBus);
 //! ```
 //!
+//! Lifeline also provides a [lifeline_static_bus!](macro.lifeline_static_bus.html) macro, which stores each channel and resource in a typed field.
+//! Static busses avoid `Any` downcasts, and report unbound messages at compile time:
+//! ```
+//! use lifeline::lifeline_static_bus;
+//! use tokio::sync::mpsc;
+//!
+//! #[derive(Debug)]
+//! pub struct MainRecv {}
+//!
+//! lifeline_static_bus!(
+//!     pub struct MainBus {
+//!         channels {
+//!             recv: MainRecv => mpsc::Sender<Self>,
+//!         }
+//!     }
+//! );
+//! ```
+//!
+//! Channel capacities can also be loaded from an environment variable, JSON, or TOML with a [CapacityConfig](./config/struct.CapacityConfig.html),
+//! so queue sizes can be tuned without recompiling.
+//!
 //! ## The Carrier
 //! [Carriers](./trait.CarryFrom.html) provide a way to move messages between busses. [Carriers](./trait.CarryFrom.html) can translate, ignore, or collect information,
 //! providing each bus with the messages that it needs.
@@ -75,6 +100,9 @@
 //! It's common for [Service::spawn](./trait.Service.html#tymethod.spawn) to return a Result.  Taking channel endpoints is a fallible operation.  Depending on the channel type, the endpoint may not be clonable.
 //! Lifeline clones endpoints when it can (e.g. for `mpsc::Sender`, `broadcast::*`, and `watch::Receiver`).  Other endpoints are taken, removed, and future calls will return an Err.
 //!
+//! Services which are spawned later (for example, on each incoming connection) can hold a clonable [BusHandle](./struct.BusHandle.html),
+//! and take channels whenever they need them.  A [WeakBusHandle](./struct.WeakBusHandle.html) does not keep the bus alive.
+//!
 //! [Service::spawn](./trait.Service.html#tymethod.spawn) takes channels from the bus synchronously, which makes errors occur predictably and early. If you get an Err on an `mpsc::Receiver`,
 //! change it's binding in the bus to `broadcast::Sender`.
 //!
@@ -89,14 +117,23 @@
 //!
 //! [Resources](./trait.Resource.html) implement the [Storage](./trait.Storage.html) trait, which is easy with the [impl_storage_clone!](./macro.impl_storage_clone.html) and [impl_storage_take!](./macro.impl_storage_take.html) macros.
 
+pub mod backpressure;
 mod bus;
 mod channel;
+pub mod config;
+mod counts;
+mod drain;
 
 #[cfg(feature = "dyn-bus")]
 pub mod dyn_bus;
 
 pub mod error;
+mod handle;
+pub mod intercept;
+pub mod lag;
+mod merge;
 pub mod prelude;
+pub mod static_bus;
 
 #[cfg(feature = "tokio-channels")]
 pub mod request;
@@ -109,11 +146,23 @@ mod storage;
 pub mod test;
 
 pub use bus::*;
-pub use channel::lifeline::{Receiver, Sender};
+pub use channel::adapter::{FilterMapReceiver, FilterReceiver, MapReceiver, WithSender};
+pub use channel::boxed::{BoxReceiver, BoxSender};
+pub use channel::coalesce::{CoalesceReceiver, CoalesceSender, Keyed};
+pub use channel::lifeline::{Receiver, Recv, Sender, SharedSender};
+pub use channel::priority::{Priority, PriorityReceiver, PrioritySender};
+pub use channel::stream::{ReceiverStream, SenderSink};
 
 pub use channel::Channel;
+
+#[cfg(feature = "flume-channels")]
+pub use channel::flume::Unbounded as FlumeUnbounded;
+#[cfg(feature = "tokio-channels")]
+pub use channel::tokio::{MpscStream, UnboundedStream};
+pub use drain::{DrainEntry, DrainReport};
+pub use handle::{BusHandle, WeakBusHandle};
+pub use merge::Merge;
 pub use service::*;
 pub use storage::Storage;
-pub use storage::*;
 
 pub use spawn::Lifeline;
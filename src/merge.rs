@@ -0,0 +1,270 @@
+use crate::{Receiver, Recv};
+use async_trait::async_trait;
+use futures_util::{
+    stream::{FusedStream, Stream},
+    StreamExt,
+};
+use std::{
+    fmt::Debug,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+type Input<E> = Pin<Box<dyn Stream<Item = E> + Send>>;
+
+/// A receiver which merges several receivers of different message types into one receiver of an enum.
+///
+/// Inputs are polled fairly (round-robin), so a busy receiver can't starve the others.
+/// The merged receiver closes when all of the inputs have closed.
+///
+/// The enum and its constructor are usually generated with [lifeline_merge!](./macro.lifeline_merge.html).
+/// Inputs can also be added with [input](#method.input):
+/// ```
+/// use lifeline::{Merge, Receiver};
+/// use tokio::sync::mpsc;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum ExampleInput {
+///     Count(usize),
+///     Name(String),
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (tx_count, rx_count) = mpsc::channel(4);
+///     let (tx_name, rx_name) = mpsc::channel(4);
+///
+///     let mut rx = Merge::new()
+///         .input(rx_count, ExampleInput::Count)
+///         .input(rx_name, ExampleInput::Name);
+///
+///     tx_count.send(1).await.unwrap();
+///     drop(tx_count);
+///     drop(tx_name);
+///
+///     assert_eq!(Some(ExampleInput::Count(1)), rx.recv().await);
+///     assert_eq!(None, rx.recv().await);
+/// }
+/// ```
+pub struct Merge<E> {
+    inputs: Vec<Option<Input<E>>>,
+    open: usize,
+    cursor: usize,
+}
+
+impl<E: Send + 'static> Merge<E> {
+    /// Constructs a merged receiver with no inputs.  A merged receiver with no inputs is closed.
+    pub fn new() -> Self {
+        Self {
+            inputs: Vec::new(),
+            open: 0,
+            cursor: 0,
+        }
+    }
+
+    /// Adds the receiver as an input.  Messages are converted into the merged type with the function (usually an enum variant).
+    pub fn input<R, T, F>(mut self, rx: R, f: F) -> Self
+    where
+        R: Receiver<T> + Send + 'static,
+        T: Send + 'static,
+        F: FnMut(T) -> E + Send + 'static,
+    {
+        self.inputs.push(Some(Box::pin(rx.into_stream().map(f))));
+        self.open += 1;
+        self
+    }
+
+    /// Returns the number of inputs which are still open
+    pub fn open_inputs(&self) -> usize {
+        self.open
+    }
+}
+
+impl<E: Send + 'static> Default for Merge<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Stream for Merge<E> {
+    type Item = E;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<E>> {
+        let this = self.get_mut();
+        let len = this.inputs.len();
+
+        // start polling after the input which last produced a value
+        for offset in 0..len {
+            let index = (this.cursor + offset) % len;
+
+            let input = match this.inputs[index].as_mut() {
+                Some(input) => input,
+                None => continue,
+            };
+
+            match input.as_mut().poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    this.cursor = index + 1;
+                    return Poll::Ready(Some(value));
+                }
+                Poll::Ready(None) => {
+                    this.inputs[index] = None;
+                    this.open -= 1;
+                }
+                Poll::Pending => {}
+            }
+        }
+
+        if this.open == 0 {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<E> FusedStream for Merge<E> {
+    fn is_terminated(&self) -> bool {
+        self.open == 0
+    }
+}
+
+#[async_trait]
+impl<E: Send + 'static> Receiver<E> for Merge<E> {
+    async fn recv(&mut self) -> Option<E> {
+        self.next().await
+    }
+
+    async fn recv_lossy(&mut self) -> Recv<E> {
+        match self.next().await {
+            Some(value) => Recv::Value(value),
+            None => Recv::Closed,
+        }
+    }
+}
+
+impl<E> Debug for Merge<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Merge")
+            .field("inputs", &self.inputs.len())
+            .field("open", &self.open)
+            .finish()
+    }
+}
+
+/// Defines an enum with one variant for each message type, and a `merge` constructor which merges receivers into a [Merge](./struct.Merge.html) of the enum.
+///
+/// The `merge` function takes one receiver for each variant, in order.  The merged receiver polls the inputs fairly, and closes when all of the inputs have closed.
+///
+/// ## Example
+/// ```
+/// use lifeline::prelude::*;
+/// use tokio::sync::mpsc;
+///
+/// lifeline_bus!(pub struct ExampleBus);
+///
+/// #[derive(Debug)]
+/// pub struct ExampleRequest(usize);
+/// impl Message<ExampleBus> for ExampleRequest {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// #[derive(Debug)]
+/// pub struct ExampleShutdown;
+/// impl Message<ExampleBus> for ExampleShutdown {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+///
+/// lifeline_merge!(
+///     #[derive(Debug)]
+///     pub enum ExampleInput {
+///         Request(ExampleRequest),
+///         Shutdown(ExampleShutdown),
+///     }
+/// );
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     use lifeline::Receiver;
+///
+///     let bus = ExampleBus::default();
+///     let mut rx = ExampleInput::merge(bus.rx::<ExampleRequest>()?, bus.rx::<ExampleShutdown>()?);
+///
+///     bus.tx::<ExampleRequest>()?.send(ExampleRequest(1)).await?;
+///     bus.tx::<ExampleShutdown>()?.send(ExampleShutdown).await?;
+///
+///     let mut requests = 0;
+///     while let Some(input) = rx.recv().await {
+///         match input {
+///             ExampleInput::Request(_request) => requests += 1,
+///             ExampleInput::Shutdown(_) => break,
+///         }
+///     }
+///
+///     assert_eq!(1, requests);
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! lifeline_merge (
+    ($(#[$meta:meta])* $vis:vis enum $name:ident { $( $variant:ident ( $ty:ty ) ),+ $(,)? }) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $( $variant($ty) ),+
+        }
+
+        impl $name {
+            /// Merges the receivers into a single receiver of this enum.  The receivers are listed in the order of the variants.
+            #[allow(non_snake_case, dead_code)]
+            $vis fn merge(
+                $( $variant: impl $crate::Receiver<$ty> + Send + 'static ),+
+            ) -> $crate::Merge<Self> {
+                $crate::Merge::new()
+                    $( .input($variant, $name::$variant) )+
+            }
+        }
+    }
+);
+
+#[cfg(all(test, feature = "tokio-channels"))]
+mod tests {
+    use super::Merge;
+    use crate::Receiver;
+    use tokio::sync::mpsc;
+
+    #[derive(Debug, PartialEq)]
+    enum Input {
+        Left(usize),
+        Right(usize),
+    }
+
+    #[tokio::test]
+    async fn merge_polls_inputs_fairly() {
+        let (tx_left, rx_left) = mpsc::channel(8);
+        let (tx_right, rx_right) = mpsc::channel(8);
+
+        for i in 0..4 {
+            tx_left.send(i).await.unwrap();
+            tx_right.send(i).await.unwrap();
+        }
+
+        drop(tx_left);
+        drop(tx_right);
+
+        let mut rx = Merge::new()
+            .input(rx_left, Input::Left)
+            .input(rx_right, Input::Right);
+
+        let mut received = Vec::new();
+        while let Some(input) = rx.recv().await {
+            received.push(input);
+        }
+
+        let expected: Vec<Input> = (0..4)
+            .flat_map(|i| vec![Input::Left(i), Input::Right(i)])
+            .collect();
+
+        assert_eq!(expected, received);
+        assert_eq!(0, rx.open_inputs());
+    }
+}
@@ -2,5 +2,7 @@
 
 pub use crate::{Bus, CarryFrom, CarryInto, Lifeline, Message, Resource, Service, Task};
 
+pub use crate::{lifeline_merge, lifeline_static_bus, static_bus::StaticBus};
+
 #[cfg(feature = "dyn-bus")]
-pub use crate::lifeline_bus;
+pub use crate::{dyn_bus::DynBus, lifeline_bus};
@@ -1,4 +1,5 @@
 use futures_util::task::AtomicWaker;
+use std::cell::RefCell;
 use std::fmt::Debug;
 use std::future::Future;
 use std::{
@@ -7,18 +8,22 @@ use std::{
         Arc,
     },
     task::Poll,
+    time::Duration,
 };
 
 use crate::error::type_name;
 use log::debug;
 use pin_project::pin_project;
 
+#[cfg(not(any(feature = "tokio-executor", feature = "async-std-executor")))]
+compile_error!("lifeline requires an executor feature: `tokio-executor` or `async-std-executor`");
+
 /// Executes the task, until the future completes, or the lifeline is dropped
 ///
 /// If the `tokio-executor` feature is enabled, then it is used to spawn the task
 ///
 /// Otherwise, if the `async-std-executor` feature is enabled, then it is used to spawn the task
-#[allow(unreachable_code)]
+#[allow(unreachable_code, clippy::needless_return)]
 pub(crate) fn spawn_task<O>(name: String, fut: impl Future<Output = O> + Send + 'static) -> Lifeline
 where
     O: Debug + Send + 'static,
@@ -40,10 +45,54 @@ where
     }
 }
 
+/// Sleeps for the duration, using the timer of the enabled executor
+#[allow(unreachable_code, clippy::needless_return)]
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(feature = "tokio-executor")]
+    {
+        tokio::time::sleep(duration).await;
+        return;
+    }
+
+    #[cfg(feature = "async-std-executor")]
+    {
+        async_std::task::sleep(duration).await;
+        return;
+    }
+}
+
 pub(crate) fn task_name<S>(name: &str) -> String {
     type_name::<S>().to_string() + "/" + name
 }
 
+thread_local! {
+    static CURRENT_TASK: RefCell<Option<Arc<str>>> = const { RefCell::new(None) };
+}
+
+/// Returns the name of the lifeline task which is being polled on this thread, if any
+pub(crate) fn current_task_name() -> Option<Arc<str>> {
+    CURRENT_TASK.with(|current| current.borrow().clone())
+}
+
+/// Records the task name while the task is polled, and restores the previous name when dropped
+struct CurrentTaskGuard {
+    previous: Option<Arc<str>>,
+}
+
+impl CurrentTaskGuard {
+    fn enter(name: Arc<str>) -> Self {
+        let previous = CURRENT_TASK.with(|current| current.borrow_mut().replace(name));
+        Self { previous }
+    }
+}
+
+impl Drop for CurrentTaskGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_TASK.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
 /// Spawns a task using the tokio executor
 #[cfg(feature = "tokio-executor")]
 fn spawn_task_tokio<F, O>(task: F)
@@ -71,7 +120,7 @@ where
 struct LifelineFuture<F: Future> {
     #[pin]
     future: F,
-    name: String,
+    name: Arc<str>,
     inner: Arc<LifelineInner>,
 }
 
@@ -80,7 +129,7 @@ impl<F: Future + Send> LifelineFuture<F> {
         debug!("START {}", &name);
 
         Self {
-            name,
+            name: name.into(),
             future,
             inner,
         }
@@ -103,6 +152,7 @@ where
         }
 
         // attempt to complete the future
+        let _task = CurrentTaskGuard::enter(self.name.clone());
         if let Poll::Ready(result) = self.as_mut().project().future.poll(cx) {
             debug!("END {} {:?}", self.name, result);
             self.inner.complete();
@@ -216,7 +266,7 @@ mod tests {
 
     use std::{future::Future, task::Poll};
 
-    use super::spawn_task;
+    use super::{current_task_name, spawn_task};
     use crate::{assert_completes, assert_times_out};
 
     struct Pending {}
@@ -249,4 +299,16 @@ mod tests {
             lifeline.await;
         });
     }
+
+    #[tokio::test]
+    async fn lifeline_records_current_task_name() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let _lifeline = spawn_task("test_name".to_string(), async move {
+            tx.send(current_task_name()).ok();
+        });
+
+        let name = rx.await.expect("task completes");
+        assert_eq!(Some("test_name"), name.as_deref());
+        assert_eq!(None, current_task_name());
+    }
 }
@@ -0,0 +1,102 @@
+//! The StaticBus implementation used by `lifeline_static_bus!`, and typed slot storage.
+mod macros;
+mod slot;
+
+use crate::{
+    bus::{Message, Resource},
+    error::{type_name, AlreadyLinkedError},
+    Bus, Channel,
+};
+
+pub use slot::{ChannelSlot, ResourceSlot};
+
+/// An extension trait which defines operations on a StaticBus, which stores each channel and resource in its own typed field.
+///
+/// StaticBus implementations are created using the `lifeline_static_bus!` macro.  Unlike a [DynBus](../dyn_bus/trait.DynBus.html),
+/// a StaticBus does not use `Any` downcasts, and messages which are not bound on the bus produce a compile error.
+pub trait StaticBus: Bus {
+    /// Stores an manually constructed Receiver on the bus, for the provided message type.
+    ///
+    /// If the message channel has already been linked (from a call to `bus.rx`, `bus.tx`, or `bus.store_*`), returns an error.
+    fn store_rx<Msg>(&self, rx: <Msg::Channel as Channel>::Rx) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<Self> + 'static,
+    {
+        channel_slot::<Self, Msg>(self).store::<Self, Msg>(Some(rx), None)
+    }
+
+    /// Stores an manually constructed Sender on the bus, for the provided message type.
+    ///
+    /// If the message channel has already been linked (from a call to `bus.rx`, `bus.tx`, or `bus.store_*`), returns an error.
+    fn store_tx<Msg>(&self, tx: <Msg::Channel as Channel>::Tx) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<Self> + 'static,
+    {
+        channel_slot::<Self, Msg>(self).store::<Self, Msg>(None, Some(tx))
+    }
+
+    /// Stores a channel pair on the bus, for the provided message type.
+    ///
+    /// If the message channel has already been linked (from a call to `bus.rx`, `bus.tx`, or `bus.store_*`), returns an error.
+    fn store_channel<Msg>(
+        &self,
+        rx: <Msg::Channel as Channel>::Rx,
+        tx: <Msg::Channel as Channel>::Tx,
+    ) -> Result<(), AlreadyLinkedError>
+    where
+        Msg: Message<Self> + 'static,
+    {
+        channel_slot::<Self, Msg>(self).store::<Self, Msg>(Some(rx), Some(tx))
+    }
+
+    /// Returns the number of messages of the type which have been dropped by the backpressure policy.
+    ///
+    /// Policies are configured with [Bus::backpressure](../trait.Bus.html#method.backpressure).
+    fn dropped_count<Msg>(&self) -> u64
+    where
+        Msg: Message<Self> + 'static,
+    {
+        channel_slot::<Self, Msg>(self).dropped_count::<Msg>()
+    }
+
+    /// Stores a resource on the bus.
+    ///
+    /// Resources are commonly used for clonable configuration structs, or takeable resources such as websocket connections.
+    fn store_resource<R: Resource<Self>>(&self, resource: R) {
+        resource_slot::<Self, R>(self).store::<Self>(resource)
+    }
+}
+
+/// Returns the typed channel slot for the message.  Used by the `lifeline_static_bus!` macro.
+///
+/// Panics if the `Message` implementation was not generated by `lifeline_static_bus!`.
+#[doc(hidden)]
+pub fn channel_slot<B, Msg>(bus: &B) -> &ChannelSlot<Msg::Channel>
+where
+    Msg: Message<B>,
+{
+    Msg::static_slot(bus).unwrap_or_else(|| {
+        panic!(
+            "{} is not bound on {}.  Messages must be bound in the lifeline_static_bus! macro.",
+            type_name::<Msg>(),
+            type_name::<B>()
+        )
+    })
+}
+
+/// Returns the typed resource slot for the resource.  Used by the `lifeline_static_bus!` macro.
+///
+/// Panics if the `Resource` implementation was not generated by `lifeline_static_bus!`.
+#[doc(hidden)]
+pub fn resource_slot<B, Res>(bus: &B) -> &ResourceSlot<Res>
+where
+    Res: Resource<B>,
+{
+    Res::static_slot(bus).unwrap_or_else(|| {
+        panic!(
+            "{} is not bound on {}.  Resources must be bound in the lifeline_static_bus! macro.",
+            type_name::<Res>(),
+            type_name::<B>()
+        )
+    })
+}
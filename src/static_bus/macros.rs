@@ -0,0 +1,210 @@
+/// Defines a static lifeline bus: its struct with one typed field per channel and resource, the `Message` and `Resource` impls, and the `Bus` impl.
+///
+/// Unlike [lifeline_bus!](./macro.lifeline_bus.html), the static bus does not store endpoints as `Box<dyn Any>`.
+/// Each channel is stored in its own field, which avoids hashing and downcasts when endpoints are taken.
+/// Messages are bound inside the macro, so taking a message which is not bound on the bus is a compile error.
+///
+/// ## Examples
+/// ```
+/// use lifeline::prelude::*;
+/// use lifeline::impl_storage_clone;
+/// use tokio::sync::{broadcast, mpsc};
+///
+/// #[derive(Debug)]
+/// pub struct ExampleRecv {}
+///
+/// #[derive(Debug, Clone)]
+/// pub struct ExampleSend {}
+///
+/// #[derive(Debug, Clone)]
+/// pub struct ExampleConfig {}
+/// impl_storage_clone!(ExampleConfig);
+///
+/// lifeline_static_bus!(
+///     pub struct ExampleBus {
+///         channels {
+///             recv: ExampleRecv => mpsc::Sender<Self>,
+///             send: ExampleSend => broadcast::Sender<Self>,
+///         }
+///         resources {
+///             config: ExampleConfig,
+///         }
+///     }
+/// );
+///
+/// fn main() -> anyhow::Result<()> {
+///     let bus = ExampleBus::default();
+///     bus.store_resource(ExampleConfig {});
+///
+///     let tx = bus.tx::<ExampleRecv>()?;
+///     let rx = bus.rx::<ExampleSend>()?;
+///     let config = bus.resource::<ExampleConfig>()?;
+///
+///     assert!(bus.rx_available::<ExampleSend>());
+///     assert!(bus.rx_available::<ExampleRecv>());
+///     assert!(bus.resource_available::<ExampleConfig>());
+///     Ok(())
+/// }
+/// ```
+///
+/// Messages which are not bound on the bus produce a compile error:
+/// ```compile_fail
+/// use lifeline::prelude::*;
+/// use tokio::sync::mpsc;
+///
+/// #[derive(Debug)]
+/// pub struct Bound {}
+///
+/// #[derive(Debug)]
+/// pub struct Unbound {}
+///
+/// lifeline_static_bus!(
+///     pub struct ExampleBus {
+///         channels {
+///             bound: Bound => mpsc::Sender<Self>,
+///         }
+///     }
+/// );
+///
+/// let bus = ExampleBus::default();
+/// let rx = bus.rx::<Unbound>();
+/// ```
+///
+/// Messages can only be bound in the macro.  A manual `Message` implementation for a static bus produces a compile error:
+/// ```compile_fail
+/// use lifeline::prelude::*;
+/// use tokio::sync::mpsc;
+///
+/// #[derive(Debug)]
+/// pub struct Bound {}
+///
+/// #[derive(Debug)]
+/// pub struct Manual {}
+///
+/// lifeline_static_bus!(
+///     pub struct ExampleBus {
+///         channels {
+///             bound: Bound => mpsc::Sender<Self>,
+///         }
+///     }
+/// );
+///
+/// impl Message<ExampleBus> for Manual {
+///     type Channel = mpsc::Sender<Self>;
+/// }
+/// ```
+#[macro_export]
+macro_rules! lifeline_static_bus (
+    (
+        $vis:vis struct $name:ident {
+            $( channels {
+                $( $field:ident : $msg:ty => $chan:ty ),* $(,)?
+            } )?
+            $( resources {
+                $( $res_field:ident : $res:ty ),* $(,)?
+            } )?
+        }
+    ) => {
+        #[derive(Debug, Default)]
+        $vis struct $name {
+            $( $(
+                $field: $crate::static_bus::ChannelSlot<<$msg as $crate::Message<$name>>::Channel>,
+            )* )?
+            $( $(
+                $res_field: $crate::static_bus::ResourceSlot<$res>,
+            )* )?
+        }
+
+        $( $(
+            impl $crate::__private::MessageBinding<$name> for $msg {}
+
+            impl $crate::Message<$name> for $msg {
+                type Channel = $chan;
+
+                fn static_slot(bus: &$name) -> Option<&$crate::static_bus::ChannelSlot<Self::Channel>> {
+                    Some(&bus.$field)
+                }
+            }
+        )* )?
+
+        $( $(
+            impl $crate::__private::ResourceBinding<$name> for $res {}
+
+            impl $crate::Resource<$name> for $res {
+                fn static_slot(bus: &$name) -> Option<&$crate::static_bus::ResourceSlot<Self>> {
+                    Some(&bus.$res_field)
+                }
+            }
+        )* )?
+
+        impl $crate::Bus for $name {
+            fn capacity<Msg>(&self, capacity: usize) -> Result<(), $crate::error::AlreadyLinkedError>
+                where Msg: $crate::Message<Self> + 'static
+            {
+                $crate::static_bus::channel_slot::<Self, Msg>(self).capacity::<Self, Msg>(capacity)
+            }
+
+            fn backpressure<Msg>(&self, policy: $crate::backpressure::BackpressurePolicy) -> Result<(), $crate::error::AlreadyLinkedError>
+                where Msg: $crate::Message<Self> + 'static
+            {
+                $crate::static_bus::channel_slot::<Self, Msg>(self).backpressure::<Self, Msg>(policy)
+            }
+
+            fn rx<Msg>(&self) -> Result<<Msg::Channel as $crate::Channel>::Rx, $crate::error::TakeChannelError>
+                where Msg: $crate::Message<Self> + 'static
+            {
+                $crate::static_bus::channel_slot::<Self, Msg>(self).clone_rx::<Self, Msg>()
+            }
+
+            fn tx<Msg>(&self) -> Result<<Msg::Channel as $crate::Channel>::Tx, $crate::error::TakeChannelError>
+                where Msg: $crate::Message<Self> + 'static
+            {
+                $crate::static_bus::channel_slot::<Self, Msg>(self).clone_tx::<Self, Msg>()
+            }
+
+            fn resource<Res>(&self) -> Result<Res, $crate::error::TakeResourceError>
+                where Res: $crate::Resource<Self>
+            {
+                $crate::static_bus::resource_slot::<Self, Res>(self).clone_resource::<Self>()
+            }
+
+            fn relink<Msg>(&self)
+                where Msg: $crate::Message<Self> + 'static
+            {
+                $crate::static_bus::channel_slot::<Self, Msg>(self).relink::<Self, Msg>()
+            }
+
+            fn is_linked<Msg>(&self) -> bool
+                where Msg: $crate::Message<Self> + 'static
+            {
+                $crate::static_bus::channel_slot::<Self, Msg>(self).is_linked()
+            }
+
+            fn capacity_available<Msg>(&self) -> bool
+                where Msg: $crate::Message<Self> + 'static
+            {
+                $crate::static_bus::channel_slot::<Self, Msg>(self).capacity_available()
+            }
+
+            fn rx_available<Msg>(&self) -> bool
+                where Msg: $crate::Message<Self> + 'static
+            {
+                $crate::static_bus::channel_slot::<Self, Msg>(self).rx_available()
+            }
+
+            fn tx_available<Msg>(&self) -> bool
+                where Msg: $crate::Message<Self> + 'static
+            {
+                $crate::static_bus::channel_slot::<Self, Msg>(self).tx_available()
+            }
+
+            fn resource_available<Res>(&self) -> bool
+                where Res: $crate::Resource<Self>
+            {
+                $crate::static_bus::resource_slot::<Self, Res>(self).is_available()
+            }
+        }
+
+        impl $crate::static_bus::StaticBus for $name {}
+    }
+);
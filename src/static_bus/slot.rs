@@ -0,0 +1,274 @@
+use crate::{
+    backpressure::{BackpressurePolicy, DropTracker},
+    bus::Link,
+    error::{type_name, AlreadyLinkedError, TakeChannelError, TakeResourceError},
+    Channel, Storage,
+};
+
+use log::debug;
+use std::{
+    fmt::Debug,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// A typed slot for a channel on a static bus.  Stores the capacity, the backpressure policy, and the Sender/Receiver endpoints.
+///
+/// Slots are generated by the [lifeline_static_bus!](../macro.lifeline_static_bus.html) macro, one field for each message type.
+pub struct ChannelSlot<Chan: Channel> {
+    state: Mutex<ChannelState<Chan>>,
+}
+
+struct ChannelState<Chan: Channel> {
+    linked: bool,
+    capacity: Option<usize>,
+    backpressure: Option<BackpressurePolicy>,
+    drops: DropTracker,
+    tx: Option<Chan::Tx>,
+    rx: Option<Chan::Rx>,
+}
+
+impl<Chan: Channel> Default for ChannelSlot<Chan> {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(ChannelState {
+                linked: false,
+                capacity: None,
+                backpressure: None,
+                drops: DropTracker::default(),
+                tx: None,
+                rx: None,
+            }),
+        }
+    }
+}
+
+impl<Chan: Channel> Debug for ChannelSlot<Chan> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+        let string = match (state.linked, state.tx.is_some(), state.rx.is_some()) {
+            (false, _, _) => format!("ChannelSlot<{}>::Unlinked", type_name::<Chan>()),
+            (true, tx, rx) => format!(
+                "ChannelSlot<{}>::Linked {{ tx: {}, rx: {} }}",
+                type_name::<Chan>(),
+                tx,
+                rx
+            ),
+        };
+
+        f.debug_struct(string.as_str()).finish()
+    }
+}
+
+impl<Chan: Channel> ChannelSlot<Chan> {
+    /// Configures the channel capacity.  Returns an error if the capacity was already configured, or if the channel is already linked.
+    pub fn capacity<Bus, Msg>(&self, capacity: usize) -> Result<(), AlreadyLinkedError> {
+        let mut state = self.lock();
+
+        if state.linked || state.capacity.is_some() {
+            return Err(AlreadyLinkedError::new::<Bus, Msg>());
+        }
+
+        state.capacity = Some(capacity);
+
+        Ok(())
+    }
+
+    /// Configures the backpressure policy.  Returns an error if the policy was already configured, or if the channel is already linked.
+    pub fn backpressure<Bus, Msg>(
+        &self,
+        policy: BackpressurePolicy,
+    ) -> Result<(), AlreadyLinkedError> {
+        let mut state = self.lock();
+
+        if state.linked || state.backpressure.is_some() {
+            return Err(AlreadyLinkedError::new::<Bus, Msg>());
+        }
+
+        state.backpressure = Some(policy);
+
+        Ok(())
+    }
+
+    /// Returns the number of messages which have been dropped by the backpressure policy.
+    pub fn dropped_count<Msg: 'static>(&self) -> u64 {
+        self.lock().drops.count::<Msg>()
+    }
+
+    /// Takes or clones the channel receiver, using the `Channel` trait implementation.
+    /// Returns an error if the endpoint cannot be taken.
+    pub fn clone_rx<Bus, Msg>(&self) -> Result<Chan::Rx, TakeChannelError> {
+        let mut state = self.lock();
+        Self::link_if_needed::<Bus, Msg>(&mut state);
+
+        let state = &mut *state;
+        Chan::clone_rx(&mut state.rx, state.tx.as_ref())
+            .ok_or_else(|| TakeChannelError::already_taken::<Bus, Msg>(Link::Rx))
+    }
+
+    /// Takes or clones the channel sender, using the `Channel` trait implementation.
+    /// Returns an error if the endpoint cannot be taken.
+    pub fn clone_tx<Bus, Msg>(&self) -> Result<Chan::Tx, TakeChannelError> {
+        let mut state = self.lock();
+        Self::link_if_needed::<Bus, Msg>(&mut state);
+
+        Chan::clone_tx(&mut state.tx)
+            .ok_or_else(|| TakeChannelError::already_taken::<Bus, Msg>(Link::Tx))
+    }
+
+    /// Returns true if the channel has been linked.
+    pub fn is_linked(&self) -> bool {
+        self.lock().linked
+    }
+
+    /// Returns true if the capacity could be configured, because the channel is not linked, and the capacity has not been configured.
+    pub fn capacity_available(&self) -> bool {
+        let state = self.lock();
+        !state.linked && state.capacity.is_none()
+    }
+
+    /// Returns true if the receiver could be taken (or cloned), without linking the channel.
+    pub fn rx_available(&self) -> bool {
+        let state = self.lock();
+        !state.linked || Chan::rx_available(state.rx.as_ref(), state.tx.as_ref())
+    }
+
+    /// Returns true if the sender could be taken (or cloned), without linking the channel.
+    pub fn tx_available(&self) -> bool {
+        let state = self.lock();
+        !state.linked || state.tx.is_some()
+    }
+
+    /// Links a fresh channel, replacing the existing endpoints if the channel was already linked.
+    pub fn relink<Bus, Msg>(&self) {
+        let mut state = self.lock();
+        Self::link(&mut state);
+
+        debug!("{} relinked in {}", type_name::<Msg>(), type_name::<Bus>());
+    }
+
+    /// Stores the (Rx, Tx) pair, or either of them if Nones are provided.
+    /// Returns an error if the channel is already linked.
+    pub fn store<Bus, Msg>(
+        &self,
+        rx: Option<Chan::Rx>,
+        mut tx: Option<Chan::Tx>,
+    ) -> Result<(), AlreadyLinkedError> {
+        if rx.is_none() && tx.is_none() {
+            return Ok(());
+        }
+
+        let mut state = self.lock();
+        if state.linked {
+            return Err(AlreadyLinkedError::new::<Bus, Msg>());
+        }
+
+        debug!("{} stored in {}", type_name::<Msg>(), type_name::<Bus>());
+
+        if let Some(ref mut tx) = tx {
+            let policy = state.backpressure.unwrap_or_default();
+            Chan::backpressure(tx, policy, &state.drops);
+        }
+
+        state.linked = true;
+        state.tx = tx;
+        state.rx = rx;
+
+        Ok(())
+    }
+
+    fn link_if_needed<Bus, Msg>(state: &mut ChannelState<Chan>) {
+        if state.linked {
+            return;
+        }
+
+        Self::link(state);
+        debug!("{} linked in {}", type_name::<Msg>(), type_name::<Bus>());
+    }
+
+    fn link(state: &mut ChannelState<Chan>) {
+        let capacity = state.capacity.unwrap_or_else(Chan::default_capacity);
+        let (mut tx, rx) = Chan::channel(capacity);
+
+        let policy = state.backpressure.unwrap_or_default();
+        Chan::backpressure(&mut tx, policy, &state.drops);
+
+        state.linked = true;
+        state.tx = Some(tx);
+        state.rx = Some(rx);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ChannelState<Chan>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A typed slot for a resource on a static bus.
+///
+/// Slots are generated by the [lifeline_static_bus!](../macro.lifeline_static_bus.html) macro, one field for each resource type.
+pub struct ResourceSlot<Res> {
+    state: Mutex<ResourceState<Res>>,
+}
+
+struct ResourceState<Res> {
+    initialized: bool,
+    value: Option<Res>,
+}
+
+impl<Res> Default for ResourceSlot<Res> {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(ResourceState {
+                initialized: false,
+                value: None,
+            }),
+        }
+    }
+}
+
+impl<Res> Debug for ResourceSlot<Res> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+        let string = match (state.initialized, state.value.is_some()) {
+            (false, _) => format!("ResourceSlot<{}>::Uninitialized", type_name::<Res>()),
+            (true, true) => format!("ResourceSlot<{}>::Some(_)", type_name::<Res>()),
+            (true, false) => format!("ResourceSlot<{}>::Taken", type_name::<Res>()),
+        };
+
+        f.debug_struct(string.as_str()).finish()
+    }
+}
+
+impl<Res: Storage> ResourceSlot<Res> {
+    /// Takes or clones the resource, using the `Storage` trait implementation.
+    /// Returns an error if the resource is uninitialized, or if it cannot be taken.
+    pub fn clone_resource<Bus>(&self) -> Result<Res, TakeResourceError> {
+        let mut state = self.lock();
+
+        if !state.initialized {
+            return Err(TakeResourceError::uninitialized::<Bus, Res>());
+        }
+
+        Res::take_or_clone(&mut state.value).ok_or_else(|| TakeResourceError::taken::<Bus, Res>())
+    }
+
+    /// Returns true if the resource has been stored, and has not been taken.
+    pub fn is_available(&self) -> bool {
+        self.lock().value.is_some()
+    }
+
+    /// Stores the resource, overwriting it if it already exists
+    pub fn store<Bus>(&self, value: Res) {
+        let mut state = self.lock();
+
+        debug!("{} stored in {}", type_name::<Res>(), type_name::<Bus>());
+
+        state.initialized = true;
+        state.value = Some(value);
+    }
+}
+
+impl<Res> ResourceSlot<Res> {
+    fn lock(&self) -> MutexGuard<'_, ResourceState<Res>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
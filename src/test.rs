@@ -111,7 +111,6 @@ macro_rules! assert_completes {
 /// # let mut runtime = tokio::runtime::Runtime::new().unwrap();
 /// # runtime.block_on(fut);
 /// ```
-
 #[macro_export]
 macro_rules! assert_times_out {
     ($e:expr) => {